@@ -0,0 +1,44 @@
+-- an append-only log of actions performed by admins, so that
+-- questions like "who minted these coins" or "who rejected this tile"
+-- can be answered after the fact. entries are recorded by the handlers
+-- that require an admin user, alongside the request that triggered them.
+create table admin_audit (
+  id              uuid          primary key default gen_random_uuid(),
+  actor           uuid          not null references users(id),
+
+  -- a short, stable identifier of the performed action,
+  -- e.g. `wallet.inject` or `bids.reject`.
+  action          varchar(255)  not null,
+
+  -- the subject of the action (an account, a transaction, a bid, a user, etc),
+  -- if the action has one.
+  target          varchar(255)  default null,
+
+  -- the payload of the request that triggered the action, kept flexible
+  -- so changes in request schemas won't affect the audit log.
+  payload         jsonb         default null,
+
+  created_at      timestamptz   not null default now()
+);
+
+-- speeds up browsing the log in chronological order
+create index idx_admin_audit_created_at on admin_audit (created_at desc);
+
+-- speeds up filtering the log by actor
+create index idx_admin_audit_actor on admin_audit (actor, created_at desc);
+
+-- speeds up filtering the log by action or target
+create index idx_admin_audit_action on admin_audit (action, created_at desc);
+create index idx_admin_audit_target on admin_audit (target, created_at desc);
+
+-- similar to transactions, audit entries are immutable. this function
+-- and the subsequent trigger ensure entries are never altered or removed.
+create function ensure_admin_audit_append_only() returns trigger as $$
+  begin
+    raise exception 'Cannot modify or remove admin audit entries';
+  end;
+$$ language plpgsql;
+
+create trigger append_only_admin_audit
+  before update or delete on admin_audit
+  for each row execute procedure ensure_admin_audit_append_only();
@@ -0,0 +1,18 @@
+-- users can be suspended by admins, e.g. for abusive behaviour.
+-- suspended users can still log in and browse, but can't perform
+-- any write operations (bidding, offering, reacting, etc).
+--
+-- a suspension can be temporary (with `suspended_until` set) or
+-- indefinite (a ban, with `suspended_until` left null). lifting a
+-- suspension clears all of these columns.
+alter table users
+  add column suspended_at       timestamptz   default null,
+  add column suspended_until    timestamptz   default null,
+  add column suspension_reason  varchar(255)  default null,
+  add column suspended_by       uuid          default null references users(id),
+
+  -- a suspension always has a start, and can't end before it starts
+  add constraint suspension_defined
+    check ((suspended_at is not null) or (suspended_until is null and suspension_reason is null and suspended_by is null)),
+  add constraint suspension_ends_after_start
+    check ((suspended_until is null) or (suspended_until > suspended_at));
@@ -0,0 +1,30 @@
+-- regions of the grid that cannot be bid on (system tiles, reserved areas,
+-- tiles blocked for legal reasons, etc). each region is a rectangle, with
+-- both corners included. a region can be temporary (with `expires_at` set),
+-- in which case it stops blocking bids after it expires.
+create table blocked_regions (
+  id              uuid          primary key default gen_random_uuid(),
+
+  min_x           int           not null,
+  min_y           int           not null,
+  max_x           int           not null,
+  max_y           int           not null,
+
+  reason          varchar(255)  not null,
+  expires_at      timestamptz   default null,
+
+  -- the admin who blocked the region, null for
+  -- regions blocked by the system itself.
+  created_by      uuid          default null references users(id),
+  created_at      timestamptz   not null default now(),
+
+  constraint valid_region
+    check ((min_x <= max_x) and (min_y <= max_y))
+);
+
+-- speeds up fetching regions that are still in effect
+create index idx_blocked_regions_expiry on blocked_regions (expires_at);
+
+-- the origin tile was previously blocked via static configuration.
+insert into blocked_regions (min_x, min_y, max_x, max_y, reason)
+values (0, 0, 0, 0, 'system tile');
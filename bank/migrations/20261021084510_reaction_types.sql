@@ -0,0 +1,26 @@
+-- reaction types used to be a fixed enum, with a counter column per type
+-- in the summary table. to allow configuring reaction types without
+-- schema changes, reactions are now plain identifiers (validated against
+-- configuration in code), and counts are kept per (bid, reaction) pair.
+alter table reactions
+  alter column reaction type varchar(32) using reaction::text;
+
+drop type reaction_type;
+
+drop table reaction_summary;
+
+-- summary table to keep track of reaction counts per bid and reaction type
+create table reaction_summary (
+  bid_id uuid not null references bids(id) on delete cascade,
+  reaction varchar(32) not null,
+  count integer not null default 0,
+  updated_at timestamptz not null default now(),
+  primary key (bid_id, reaction),
+  check (count >= 0)
+);
+
+-- the summary is rebuilt from the reactions themselves,
+-- as they are the source of truth.
+insert into reaction_summary (bid_id, reaction, count)
+select bid_id, reaction, count(*) from reactions
+group by bid_id, reaction;
@@ -0,0 +1,11 @@
+-- speeds up ranking bids by the count of a specific reaction type
+create index idx_reaction_summary_ranking
+  on reaction_summary (reaction, count desc)
+  include (bid_id)
+  where count > 0;
+
+-- speeds up fetching bids that were ever published (and not rejected),
+-- e.g. for all-time leaderboards.
+create index idx_published_bids
+  on bids (published_at desc)
+  where published_at is not null and rejection is null;
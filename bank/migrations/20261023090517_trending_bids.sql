@@ -0,0 +1,23 @@
+-- trending bids are ranked by their recent reactions, where the weight
+-- of each reaction halves every 6 hours, so that tiles that get attention
+-- fast surface above tiles that gathered their reactions a long time ago.
+-- reactions older than 3 days have negligible weight and are ignored.
+--
+-- the ranking is computed periodically (see `bidding/reactions/trending.rs`),
+-- and only includes bids that were live at the time of computation.
+create materialized view trending_bids as
+select
+  reactions.bid_id,
+  sum(power(0.5, extract(epoch from now() - reactions.updated_at) / 21600))::float8 as score,
+  count(*)::int as reactions,
+  now() as computed_at
+from reactions
+join published_tiles tile on tile.occupant_bid = reactions.bid_id
+where reactions.updated_at > now() - interval '3 days'
+group by reactions.bid_id;
+
+-- required for refreshing the view concurrently
+create unique index idx_trending_bids_bid on trending_bids (bid_id);
+
+-- speeds up fetching the ranking
+create index idx_trending_bids_score on trending_bids (score desc);
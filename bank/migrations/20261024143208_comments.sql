@@ -0,0 +1,31 @@
+-- comments are attached to bids (and not coordinates), so that
+-- a new occupant of a tile starts with a fresh guestbook.
+create table comments (
+  id              uuid          primary key default gen_random_uuid(),
+  bid_id          uuid          not null references bids(id) on delete cascade,
+  -- the comment this comment is replying to, if any
+  parent_id       uuid          references comments(id) on delete cascade,
+  author          uuid          not null references users(id) on delete cascade,
+  content         text          not null,
+  created_at      timestamptz   not null default now(),
+  -- comments are soft-deleted, so that replies to them are kept in context.
+  -- a comment is either deleted by its author, or removed by an admin
+  -- (in which case `removed_by` is set).
+  deleted_at      timestamptz,
+  removed_by      uuid          references users(id),
+  removal_reason  text,
+
+  check (removed_by is null or deleted_at is not null)
+);
+
+-- speeds up fetching comments of a bid
+create index idx_comments_bid on comments (bid_id, created_at);
+
+-- speeds up counting recent comments of a user (for rate limiting)
+create index idx_comments_author on comments (author, created_at desc);
+
+-- bids whose owners have turned comments off
+create table comments_disabled (
+  bid_id          uuid          primary key references bids(id) on delete cascade,
+  disabled_at     timestamptz   not null default now()
+);
@@ -0,0 +1,13 @@
+-- bookmarks of tiles, synced across devices of a user.
+create table bookmarks (
+  id          uuid          primary key default gen_random_uuid(),
+  user_id     uuid          not null references users(id) on delete cascade,
+  x           integer       not null,
+  y           integer       not null,
+  label       varchar(64),
+  created_at  timestamptz   not null default now(),
+  updated_at  timestamptz   not null default now(),
+
+  -- each tile can be bookmarked once per user
+  unique (user_id, x, y)
+);
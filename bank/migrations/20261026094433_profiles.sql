@@ -0,0 +1,16 @@
+-- public profiles are opt-in: a user can set up a profile with a handle,
+-- and only when the profile is marked public, the handle, display name, etc.
+-- are shown alongside their tiles.
+create table profiles (
+  user_id       uuid          primary key references users(id) on delete cascade,
+  -- handles are stored lowercase, and are unique
+  handle        varchar(24)   not null unique,
+  display_name  varchar(64)   not null,
+  bio           varchar(280),
+  link          varchar(255),
+  public        boolean       not null default false,
+  created_at    timestamptz   not null default now(),
+  updated_at    timestamptz   not null default now(),
+
+  check (handle = lower(handle))
+);
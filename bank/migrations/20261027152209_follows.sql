@@ -0,0 +1,61 @@
+-- users can follow creators with public profiles.
+create table follows (
+  follower    uuid          not null references users(id) on delete cascade,
+  followee    uuid          not null references users(id) on delete cascade,
+  created_at  timestamptz   not null default now(),
+  primary key (follower, followee),
+  check (follower <> followee)
+);
+
+-- speeds up listing followers of a user
+create index idx_follows_followee on follows (followee, created_at desc);
+
+-- notifications are events addressed to a specific user. they are meant
+-- to be polled by clients, or picked up by whatever delivers them further
+-- (emails, push notifications, etc).
+create table notifications (
+  id          uuid          primary key default gen_random_uuid(),
+  user_id     uuid          not null references users(id) on delete cascade,
+  -- what happened, e.g. `followee.published`
+  kind        varchar(64)   not null,
+  payload     jsonb         not null,
+  created_at  timestamptz   not null default now(),
+  read_at     timestamptz
+);
+
+-- speeds up fetching notifications of a user
+create index idx_notifications_user on notifications (user_id, created_at desc);
+
+-- whenever a bid is published (either immediately, or by winning an auction),
+-- followers of the bidder are notified. this is done in the database so that
+-- no publishing path can skip it.
+create function notify_followers_on_publish() returns trigger as $$
+  begin
+    insert into notifications (user_id, kind, payload)
+    select
+      follows.follower,
+      'followee.published',
+      jsonb_build_object(
+        'bid_id', new.id,
+        'bidder', new.bidder,
+        'handle', profiles.handle,
+        'x', new.x,
+        'y', new.y
+      )
+    from follows
+    join profiles on profiles.user_id = new.bidder and profiles.public
+    where follows.followee = new.bidder;
+
+    return new;
+  end;
+$$ language plpgsql;
+
+create trigger notify_followers_on_publish
+  after update of published_at on bids
+  for each row
+  when (old.published_at is null and new.published_at is not null)
+  execute procedure notify_followers_on_publish();
+
+-- speeds up fetching recently published bids of followed users
+create index idx_bids_bidder_published on bids (bidder, published_at desc)
+  where published_at is not null and rejection is null;
@@ -0,0 +1,11 @@
+-- tips are offers from viewers of a tile to its occupant. the offers
+-- themselves live in the ledger, this table links them to the tipped bid.
+create table bid_tips (
+  tx          uuid          primary key references transactions(id),
+  bid_id      uuid          not null references bids(id) on delete cascade,
+  tipper      uuid          not null references users(id),
+  created_at  timestamptz   not null default now()
+);
+
+-- speeds up tallying tips of a bid
+create index idx_bid_tips_bid on bid_tips (bid_id);
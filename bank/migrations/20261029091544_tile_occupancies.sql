@@ -0,0 +1,33 @@
+-- `published_tiles` only keeps the current occupant of each tile. to keep
+-- the history of tiles, each time a bid occupies a tile, the window of its
+-- occupancy is recorded here. the window ends when the bid is replaced by
+-- another bid, unpublished, or rejected.
+create table tile_occupancies (
+  bid_id      uuid          primary key references bids(id) on delete cascade,
+  x           int           not null,
+  y           int           not null,
+  started_at  timestamptz   not null default now(),
+  ended_at    timestamptz,
+
+  check (ended_at is null or ended_at >= started_at)
+);
+
+-- speeds up fetching history of a tile
+create index idx_tile_occupancies_tile on tile_occupancies (x, y, started_at);
+
+-- previously published bids are backfilled: a bid's occupancy ended when it
+-- was rejected, or when the next bid was published on the same tile. bids that
+-- were unpublished (by their owners) before this point have no end time, as
+-- it wasn't tracked.
+insert into tile_occupancies (bid_id, x, y, started_at, ended_at)
+select
+  bids.id, bids.x, bids.y, bids.published_at,
+  case
+    when exists (select 1 from published_tiles tile where tile.occupant_bid = bids.id) then null
+    else coalesce(
+      (bids.rejection->>'rejected_at')::timestamptz,
+      lead(bids.published_at) over (partition by bids.x, bids.y order by bids.published_at)
+    )
+  end
+from bids
+where bids.published_at is not null;
@@ -0,0 +1,15 @@
+-- each account has a single live state (its balance), i.e. a state
+-- transaction that is neither consumed nor merged. enforcing this
+-- here guards against concurrent requests initializing the same
+-- account twice. consuming or merging the prior state happens before
+-- the new state is inserted (see consume_transaction()), so regular
+-- operations never hold two live states at once.
+drop index idx_user_balance;
+create unique index idx_user_balance
+  on transactions (receiver)
+  where is_state is true and consumed is false and merged is false;
+
+drop index idx_system_user_balance;
+create unique index idx_system_user_balance
+  on transactions (receiver_sys)
+  where is_state is true and consumed is false and merged is false;
@@ -0,0 +1,15 @@
+-- allowances are periodically paid to active users, minted by the
+-- mint system account. each user is paid at most once per period,
+-- which is enforced by claiming the (user, period) pair here before
+-- paying, so that the allowance job can safely be re-run.
+create table allowances (
+  user_id      uuid        not null references users(id) on delete cascade,
+  period_start timestamptz not null,
+  amount       integer     not null check (amount > 0),
+
+  -- the offer paying the allowance, null while it is being paid.
+  tx           uuid        default null references transactions(id),
+  created_at   timestamptz not null default now(),
+
+  primary key (user_id, period_start)
+);
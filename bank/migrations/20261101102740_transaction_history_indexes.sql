@@ -0,0 +1,11 @@
+-- speeds up fetching the history of a user, which includes
+-- transactions sent or received by them, most recent first.
+-- the latter also speeds up finding a user's balance as of
+-- some point in their history.
+create index idx_transactions_sender_created_at
+  on transactions (sender, created_at)
+  where sender is not null;
+
+create index idx_transactions_receiver_created_at
+  on transactions (receiver, created_at)
+  where receiver is not null;
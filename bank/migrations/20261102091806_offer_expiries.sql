@@ -0,0 +1,11 @@
+-- offers can optionally expire, after which they are rejected back
+-- to their sender by the expiry job, so coins don't get stuck in
+-- forgotten offers. expiries are kept apart from transactions, as
+-- the latter are immutable and expiry is not part of the ledger.
+create table offer_expiries (
+  tx         uuid        primary key references transactions(id),
+  expires_at timestamptz not null
+);
+
+-- speeds up finding expired offers.
+create index idx_offer_expiries_expires_at on offer_expiries (expires_at);
@@ -0,0 +1,41 @@
+-- injections used to create a throw-away `tmp-<uuid>` system account each,
+-- and now happen through the mint account instead. this folds these accounts
+-- into the mint's history: states of such accounts left over from injections
+-- that failed half-way are merged into the mint's balance, and then the accounts
+-- are relabeled as the mint. only account labels change, values and the
+-- transaction graph stay as they were.
+alter table transactions disable trigger immutable_transactions;
+
+do $$
+declare
+  orphan transactions%rowtype;
+  mint transactions%rowtype;
+begin
+  for orphan in
+    select * from transactions
+    where receiver_sys like 'tmp-%' and is_state and not consumed and not merged
+    order by created_at
+  loop
+    select * into mint from transactions
+    where receiver_sys = 'mint' and is_state and not consumed and not merged;
+
+    insert into transactions (
+      sender_sys, receiver_sys, consumes, consumed_value, merges, merged_value, note, issued_by
+    ) values (
+      'mint', 'mint', orphan.id, orphan.consumed_value + orphan.merged_value,
+      mint.id, coalesce(mint.consumed_value + mint.merged_value, 0),
+      'folded ' || orphan.receiver_sys, orphan.issued_by
+    );
+  end loop;
+end $$;
+
+update transactions set sender_sys = 'mint' where sender_sys like 'tmp-%';
+update transactions set receiver_sys = 'mint' where receiver_sys like 'tmp-%';
+
+alter table transactions enable trigger immutable_transactions;
+
+-- coins are issued by states of the mint account without a sender,
+-- this index speeds up tallying them.
+create index idx_mint_issuances
+  on transactions (created_at)
+  where receiver_sys = 'mint' and sender is null and sender_sys is null;
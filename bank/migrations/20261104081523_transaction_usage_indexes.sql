@@ -0,0 +1,9 @@
+-- speeds up finding the transaction consuming (or merging) a given
+-- transaction, e.g. to tell whether it was live at some point in time.
+create index idx_transactions_consumes
+  on transactions (consumes)
+  where consumes is not null;
+
+create index idx_transactions_merges
+  on transactions (merges)
+  where merges is not null;
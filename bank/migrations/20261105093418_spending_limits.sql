@@ -0,0 +1,11 @@
+-- spending limits set for specific users. limits set by admins
+-- replace the configured ones, while limits set by users can only
+-- lower them. null means the limit isn't set, and zero means unlimited.
+create table spending_limits (
+  user_id               uuid         primary key references users(id) on delete cascade,
+  admin_daily           integer      default null check (admin_daily >= 0),
+  admin_per_transaction integer      default null check (admin_per_transaction >= 0),
+  user_daily            integer      default null check (user_daily >= 0),
+  user_per_transaction  integer      default null check (user_per_transaction >= 0),
+  updated_at            timestamptz  not null default now()
+);
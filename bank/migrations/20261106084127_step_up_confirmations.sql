@@ -0,0 +1,11 @@
+-- passkey confirmations of sensitive operations (e.g. large offers), each
+-- bound to a single operation through its digest. a confirmation can be
+-- used once, and only shortly after it is given (see `auth/step_up.rs`).
+create table step_up_confirmations (
+  id           uuid         primary key default gen_random_uuid(),
+  user_id      uuid         not null references users(id) on delete cascade,
+  operation    text         not null,
+  confirmed_at timestamptz  not null default now(),
+  expires_at   timestamptz  not null,
+  used_at      timestamptz  default null
+);
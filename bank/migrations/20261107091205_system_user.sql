@@ -0,0 +1,6 @@
+-- transactions must be issued by some user. scheduled jobs that don't act on
+-- behalf of any particular user (e.g. paying allowances) issue their transactions
+-- as this system user, which has no passkeys and so can't be authenticated as.
+insert into users (id, first_name, last_name, email, email_verified_at)
+values ('00000000-0000-0000-0000-000000000000', 'System', 'Jobs', 'system', now())
+on conflict do nothing;
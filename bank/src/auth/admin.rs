@@ -86,3 +86,26 @@ where
     }
   }
 }
+
+///
+/// Extracting an `Option<AdminUser>` never fails: it is `None` unless
+/// the request is made by an admin, e.g. for handlers that return more
+/// details to admins.
+///
+impl<S> axum::extract::OptionalFromRequestParts<S> for AdminUser
+where
+  S: Send + Sync,
+{
+  type Rejection = Response;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &S,
+  ) -> Result<Option<Self>, Self::Rejection> {
+    Ok(
+      <Self as FromRequestParts<S>>::from_request_parts(parts, state)
+        .await
+        .ok(),
+    )
+  }
+}
@@ -0,0 +1,167 @@
+use axum::{
+  extract::{Extension, Json, Query},
+  response::IntoResponse,
+  routing::get,
+  Router,
+};
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+use tower_http::cors::{Any, CorsLayer};
+
+use super::admin::AdminUser;
+use super::error::AuthError;
+use super::user::AuthenticatedUser;
+
+const MAX_AUDIT_PAGE: u32 = 100;
+
+///
+/// Represents an entry in the admin audit log, i.e.
+/// an action performed by some admin user.
+///
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuditEntry {
+  pub id: Uuid,
+  pub actor: Uuid,
+  pub action: String,
+  pub target: Option<String>,
+  pub payload: Option<Value>,
+  pub created_at: DateTime<Utc>,
+}
+
+///
+/// Filters for querying the audit log. All filters are optional,
+/// and are combined when provided.
+///
+#[derive(Deserialize, Debug, Default)]
+pub struct AuditFilter {
+  pub actor: Option<Uuid>,
+  pub action: Option<String>,
+  pub target: Option<String>,
+  pub since: Option<DateTime<Utc>>,
+  pub until: Option<DateTime<Utc>>,
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+///
+/// An append-only log of admin actions. Handlers that require an
+/// `AdminUser` should record what they do here, so that
+/// the action can be traced back to the admin performing it.
+/// Actions are recorded before they are performed, and not performed
+/// if they can't be recorded, so no admin action goes unaudited (entries
+/// are thus attempted actions, which might have failed afterwards).
+///
+/// ```rs
+/// pub async fn my_admin_handler(
+///   Extension(audit): Extension<AuditLog>,
+///   AdminUser(user): AdminUser,
+///   Json(body): Json<MyRequestBody>,
+/// ) -> Result<impl IntoResponse, MyError> {
+///   audit
+///     .record(&user, "my.action", Some(target), &body)
+///     .await
+///     .map_err(|_| MyError::Unknown)?;
+///   // ... perform the action
+/// }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+  pool: Pool<Postgres>,
+}
+
+impl AuditLog {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  ///
+  /// Records an action performed by given admin. Should be called before
+  /// performing the action, which should fail if it can't be recorded.
+  ///
+  /// ### Params:
+  /// - `actor`: the admin performing the action
+  /// - `action`: a short identifier of the action, e.g. `wallet.inject`
+  /// - `target`: the subject of the action, if any
+  /// - `payload`: the request payload that triggered the action
+  ///
+  pub async fn record(
+    &self,
+    actor: &AuthenticatedUser,
+    action: &str,
+    target: Option<String>,
+    payload: &impl Serialize,
+  ) -> Result<(), sqlx::Error> {
+    let payload = serde_json::to_value(payload).ok();
+
+    sqlx::query!(
+      "
+        insert into admin_audit (actor, action, target, payload)
+        values ($1, $2, $3, $4)
+      ",
+      actor.id,
+      action,
+      target,
+      payload,
+    )
+    .execute(&self.pool)
+    .await
+    .map_err(|err| {
+      error!(
+        "Failed to record admin action {action} by {}: {err:?}",
+        actor.email
+      );
+      err
+    })?;
+
+    Ok(())
+  }
+
+  pub async fn find(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    sqlx::query_as!(
+      AuditEntry,
+      "
+        select * from admin_audit
+        where ($1::uuid is null or actor = $1)
+          and ($2::varchar is null or action = $2)
+          and ($3::varchar is null or target = $3)
+          and ($4::timestamptz is null or created_at >= $4)
+          and ($5::timestamptz is null or created_at < $5)
+        order by created_at desc
+        offset $6 limit $7
+      ",
+      filter.actor,
+      filter.action,
+      filter.target,
+      filter.since,
+      filter.until,
+      i64::from(filter.offset.unwrap_or(0)),
+      i64::from(filter.limit.unwrap_or(32).min(MAX_AUDIT_PAGE)),
+    )
+    .fetch_all(&self.pool)
+    .await
+  }
+}
+
+async fn audit(
+  Extension(audit): Extension<AuditLog>,
+  Query(filter): Query<AuditFilter>,
+  AdminUser(_): AdminUser,
+) -> Result<impl IntoResponse, AuthError> {
+  audit.find(&filter).await.map(Json).map_err(|err| {
+    error!("Failed to query admin audit log: {err:?}");
+    AuthError::Unknown
+  })
+}
+
+pub fn router() -> Router {
+  let cors = CorsLayer::new()
+    .allow_methods(Any)
+    .allow_headers(Any)
+    .allow_origin(Any);
+
+  Router::new().route("/audit", get(audit)).layer(cors)
+}
@@ -35,12 +35,17 @@ pub enum AuthError {
   /// The user doesn't have enough permissions (e.g. they aren't admin)
   #[error("Insufficient permissions")]
   InsufficientPermissions,
+  /// The user is suspended, and can't perform the requested operation
+  #[error("User suspended")]
+  Suspended,
+  /// The requested suspension is invalid (e.g. it would end before it starts)
+  #[error("Invalid suspension")]
+  InvalidSuspension,
   /// Failed to deserialise session
   #[error("Deserialising session failed: {0}")]
   InvalidSessionState(#[from] tower_sessions::session::Error),
 
   /// The user has no passkeys
-  #[allow(dead_code)]
   #[error("User has no credentials")]
   UserHasNoCredentials,
 }
@@ -63,6 +68,8 @@ impl IntoResponse for AuthError {
       ),
       AuthError::TooManyAttempts => (StatusCode::TOO_MANY_REQUESTS, "Too many attempts"),
       AuthError::InsufficientPermissions => (StatusCode::FORBIDDEN, "Insufficient permissions"),
+      AuthError::Suspended => (StatusCode::FORBIDDEN, "User suspended"),
+      AuthError::InvalidSuspension => (StatusCode::BAD_REQUEST, "Invalid suspension"),
     })
     .into_response()
   }
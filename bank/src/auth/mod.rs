@@ -16,16 +16,22 @@ use tower_sessions::{
 use webauthn_rs::prelude::*;
 
 pub mod admin;
+pub mod audit;
 mod authenticate;
 mod email;
 pub mod error;
 mod passkeys;
 mod register;
+pub mod step_up;
 mod storage;
+pub mod suspension;
+#[cfg(test)]
+mod tests;
 pub mod user;
 mod users;
 
 pub use error::AuthError;
+pub use suspension::ActiveUser;
 pub use user::AuthenticatedUser;
 
 ///
@@ -33,6 +39,7 @@ pub use user::AuthenticatedUser;
 /// - registration
 /// - login
 /// - passkey management
+/// - step-up (passkey) confirmation of sensitive operations
 /// - email verification (and sign in)
 ///
 pub fn router(db: &Pool<Postgres>) -> Router {
@@ -113,6 +120,8 @@ pub fn router(db: &Pool<Postgres>) -> Router {
     .route("/passkeys/start", post(passkeys::start_adding))
     .route("/passkeys/finish", post(passkeys::finish_adding))
     .route("/passkeys/{id}", delete(passkeys::remove))
+    .route("/step-up/start", post(step_up::start))
+    .route("/step-up/finish", post(step_up::finish))
     .nest("/email", email::router())
     .layer(Extension(Arc::new(webauthn)))
     .layer(session)
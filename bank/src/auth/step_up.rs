@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use axum::{
+  extract::{Extension, Json},
+  http::HeaderMap,
+  response::IntoResponse,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::Postgres, Pool};
+use tower_sessions::Session;
+use webauthn_rs::prelude::*;
+
+use super::error::AuthError;
+use super::storage::AuthStorage;
+use super::suspension::ActiveUser;
+use super::user::AuthenticatedUser;
+
+///
+/// The header carrying the token of a step-up confirmation
+/// (see `step_up::finish()`) for a sensitive operation.
+///
+pub const STEP_UP_HEADER: &str = "x-step-up";
+
+///
+/// How long a step-up confirmation can be used after it is given.
+///
+const CONFIRMATION_TTL: Duration = Duration::minutes(5);
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+  let mut hasher = Sha256::new();
+  hasher.update(data);
+  hasher.finalize().into()
+}
+
+///
+/// Returns the digest of given operation. Keys of JSON objects are
+/// sorted, so the digest doesn't depend on how the operation was written.
+///
+pub(super) fn digest(operation: &Value) -> String {
+  URL_SAFE_NO_PAD.encode(sha256(operation.to_string().as_bytes()))
+}
+
+///
+/// Keeps track of step-up confirmations, i.e. fresh passkey assertions users give
+/// for sensitive operations (e.g. large offers), on top of their (long lived) token.
+/// Each confirmation is bound to a single operation, described as a JSON object, e.g.
+/// ```json
+/// {
+///   "action": "wallet.send",
+///   "recipient": { "email": "someone@example.com" },
+///   "amount": 50,
+///   "note": null,
+///   "expires_at": null
+/// }
+/// ```
+/// Handlers requiring a confirmation build the same object from the request they're
+/// handling, and verify the confirmation against it (see `StepUps::verify()`).
+///
+#[derive(Debug, Clone)]
+pub struct StepUps {
+  pool: Pool<Postgres>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StepUpConfirmation {
+  pub token: Uuid,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl StepUps {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  pub(super) async fn confirm(
+    &self,
+    user_id: &Uuid,
+    operation: &str,
+  ) -> Result<StepUpConfirmation, sqlx::Error> {
+    sqlx::query_as!(
+      StepUpConfirmation,
+      "
+        insert into step_up_confirmations (user_id, operation, expires_at)
+        values ($1, $2, $3)
+        returning id as token, expires_at
+      ",
+      user_id,
+      operation,
+      Utc::now() + CONFIRMATION_TTL,
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+
+  ///
+  /// Checks whether the request (given its headers) carries a step-up confirmation
+  /// of given operation by given user, using up the confirmation if so.
+  ///
+  /// ### Returns:
+  /// `true` if the operation is confirmed, `false` if the confirmation is missing,
+  /// expired, already used, or given for another user or operation.
+  ///
+  pub async fn verify(
+    &self,
+    headers: &HeaderMap,
+    user: &AuthenticatedUser,
+    operation: &Value,
+  ) -> Result<bool, sqlx::Error> {
+    let Some(token) = headers
+      .get(STEP_UP_HEADER)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| Uuid::parse_str(value).ok())
+    else {
+      return Ok(false);
+    };
+
+    let res = sqlx::query!(
+      "
+        update step_up_confirmations set used_at = now()
+        where id = $1 and user_id = $2 and operation = $3
+          and used_at is null and expires_at > now()
+      ",
+      token,
+      user.id,
+      digest(operation),
+    )
+    .execute(&self.pool)
+    .await?;
+
+    Ok(res.rows_affected() == 1)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StepUpState {
+  user_id: Uuid,
+  operation: String,
+  authentication: PasskeyAuthentication,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StepUpStartBody {
+  pub operation: Value,
+}
+
+///
+/// Starts a [webauthn](https://webauthn.io) passkey confirmation of given operation,
+/// similar to authentication (see `authenticate::start()`), except that:
+///
+/// 1. Only passkeys of the current user are allowed,
+/// 2. The challenge is derived from the operation (and a random nonce), so the
+///    signature of the authenticator is bound to the operation,
+/// 3. The challenge is stored in user's session alongside the operation.
+///
+/// ```js
+/// const operation = { action: 'wallet.send', recipient: { email }, amount: 50, note, expires_at: null }
+/// const res = await fetch('.../step-up/start', {
+///   method: 'POST',
+///   credentials: 'include',
+///   body: JSON.stringify({ operation }),
+///   ...
+/// })
+/// ```
+///
+pub async fn start(
+  Extension(webauthn): Extension<Arc<Webauthn>>,
+  Extension(storage): Extension<AuthStorage>,
+  session: Session,
+  ActiveUser(user): ActiveUser,
+  Json(body): Json<StepUpStartBody>,
+) -> Result<impl IntoResponse, AuthError> {
+  //
+  // 1. Create the challenge for passkeys of the current user
+  //
+  let passkeys = storage
+    .get_passkeys(user.id)
+    .await
+    .map_err(|err| {
+      error!("Failed to fetch passkeys of {}: {err:?}", user.email);
+      AuthError::Unknown
+    })?
+    .into_iter()
+    .map(|key| key.passkey_data)
+    .collect::<Vec<_>>();
+  if passkeys.is_empty() {
+    return Err(AuthError::UserHasNoCredentials);
+  }
+
+  let (credential_options, auth_state) = webauthn
+    .start_passkey_authentication(&passkeys)
+    .map_err(|_| AuthError::Unknown)?;
+
+  //
+  // 2. Replace the challenge with one derived from the operation. webauthn_rs
+  //    doesn't support custom challenges, so similar to PRF extensions, we
+  //    modify both the options and the state manually.
+  //
+  let operation = digest(&body.operation);
+  let nonce: [u8; 32] = rand::random();
+  let challenge =
+    URL_SAFE_NO_PAD.encode(sha256(&[nonce.as_slice(), operation.as_bytes()].concat()));
+
+  let mut opts_json = serde_json::to_value(credential_options).map_err(|_| AuthError::Unknown)?;
+  let mut state_json = serde_json::to_value(auth_state).map_err(|_| AuthError::Unknown)?;
+  opts_json["publicKey"]["challenge"] = json!(challenge);
+  state_json["ast"]["challenge"] = json!(challenge);
+  let authentication = serde_json::from_value(state_json).map_err(|_| AuthError::Unknown)?;
+
+  //
+  // 3. Store the challenge and the operation in user's session
+  //
+  session
+    .insert(
+      "step_up_state",
+      StepUpState {
+        user_id: user.id,
+        operation,
+        authentication,
+      },
+    )
+    .await?;
+
+  Ok(Json(opts_json))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StepUpFinishBody {
+  pub credential: PublicKeyCredential,
+}
+
+///
+/// Finishes a [webauthn](https://webauthn.io) passkey confirmation of an operation
+/// (see `step_up::start()`), returning a token that confirms the operation. The token
+/// should be sent in the `X-Step-Up` header of the request performing the operation,
+/// and can be used only once, within a few minutes.
+///
+pub async fn finish(
+  Extension(webauthn): Extension<Arc<Webauthn>>,
+  Extension(storage): Extension<AuthStorage>,
+  Extension(step_ups): Extension<StepUps>,
+  session: Session,
+  ActiveUser(user): ActiveUser,
+  Json(body): Json<StepUpFinishBody>,
+) -> Result<impl IntoResponse, AuthError> {
+  //
+  // 1. Fetch the expected challenge from session (once)
+  //
+  let Some(state) = session.remove::<StepUpState>("step_up_state").await? else {
+    return Err(AuthError::CorruptSession);
+  };
+  if state.user_id != user.id {
+    return Err(AuthError::CorruptSession);
+  }
+
+  //
+  // 2. Verify the signature using the passkey's public key
+  //
+  let Ok(auth_result) =
+    webauthn.finish_passkey_authentication(&body.credential, &state.authentication)
+  else {
+    return Err(AuthError::InvalidCredentials);
+  };
+
+  //
+  // 3. If need be, update passkey information
+  //
+  if auth_result.needs_update() {
+    for key in storage.get_passkeys(user.id).await.unwrap_or_default() {
+      let mut passkey = key.passkey_data;
+      if passkey.update_credential(&auth_result) == Some(true) {
+        let _ = storage.update_passkey(user.id, &passkey).await;
+      }
+    }
+  }
+
+  //
+  // 4. Record the confirmation, and return its token
+  //
+  step_ups
+    .confirm(&user.id, &state.operation)
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to confirm operation of {}: {err:?}", user.email);
+      AuthError::Unknown
+    })
+}
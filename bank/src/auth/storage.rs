@@ -10,6 +10,10 @@ pub struct StoredUser {
   pub first_name: String,
   pub last_name: String,
   pub email_verified_at: Option<DateTime<Utc>>,
+  pub suspended_at: Option<DateTime<Utc>>,
+  pub suspended_until: Option<DateTime<Utc>>,
+  pub suspension_reason: Option<String>,
+  pub suspended_by: Option<Uuid>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
 }
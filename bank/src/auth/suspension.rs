@@ -0,0 +1,184 @@
+use axum::{
+  extract::{Extension, FromRequestParts},
+  http::request::Parts,
+  response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+
+use super::error::AuthError;
+use super::user::AuthenticatedUser;
+
+///
+/// Represents the suspension of a user. A suspension with no end
+/// (`suspended_until` being `None`) is effectively a ban.
+///
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Suspension {
+  pub user_id: Uuid,
+  pub reason: String,
+  pub suspended_at: DateTime<Utc>,
+  pub suspended_until: Option<DateTime<Utc>>,
+  pub suspended_by: Uuid,
+}
+
+///
+/// Keeps track of user suspensions. Suspended users can't
+/// perform write operations until their suspension expires
+/// or is lifted by an admin.
+///
+#[derive(Debug, Clone)]
+pub struct Suspensions {
+  pool: Pool<Postgres>,
+}
+
+impl Suspensions {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  ///
+  /// Returns the currently active suspension of given user, if any.
+  /// Expired suspensions are not returned.
+  ///
+  pub async fn find_active(&self, user_id: &Uuid) -> Result<Option<Suspension>, sqlx::Error> {
+    let suspension = sqlx::query_as!(
+      Suspension,
+      r#"
+        select
+          id as user_id,
+          suspension_reason as "reason!",
+          suspended_at as "suspended_at!",
+          suspended_until,
+          suspended_by as "suspended_by!"
+        from users
+        where id = $1
+          and suspended_at is not null
+          and (suspended_until is null or suspended_until > now())
+      "#,
+      user_id
+    )
+    .fetch_optional(&self.pool)
+    .await?;
+
+    Ok(suspension)
+  }
+
+  ///
+  /// Suspends given user, until given time or indefinitely. An existing
+  /// suspension of the user will be replaced.
+  ///
+  /// ### Params:
+  /// - `user_id`: the user to suspend
+  /// - `reason`: the reason for suspension
+  /// - `until`: when the suspension expires, `None` for an indefinite suspension
+  /// - `admin`: the admin suspending the user
+  ///
+  /// ### Returns:
+  /// The suspension, or `RowNotFound` if the user doesn't exist.
+  ///
+  pub async fn suspend(
+    &self,
+    user_id: &Uuid,
+    reason: &str,
+    until: Option<DateTime<Utc>>,
+    admin: &AuthenticatedUser,
+  ) -> Result<Suspension, sqlx::Error> {
+    sqlx::query_as!(
+      Suspension,
+      r#"
+        update users set
+          suspended_at = now(),
+          suspended_until = $2,
+          suspension_reason = $3,
+          suspended_by = $4,
+          updated_at = now()
+        where id = $1
+        returning
+          id as user_id,
+          suspension_reason as "reason!",
+          suspended_at as "suspended_at!",
+          suspended_until,
+          suspended_by as "suspended_by!"
+      "#,
+      user_id,
+      until,
+      reason,
+      admin.id
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+
+  ///
+  /// Lifts the suspension of given user. Returns `RowNotFound`
+  /// if the user isn't suspended.
+  ///
+  pub async fn lift(&self, user_id: &Uuid) -> Result<(), sqlx::Error> {
+    let res = sqlx::query!(
+      "
+        update users set
+          suspended_at = null,
+          suspended_until = null,
+          suspension_reason = null,
+          suspended_by = null,
+          updated_at = now()
+        where id = $1 and suspended_at is not null
+      ",
+      user_id
+    )
+    .execute(&self.pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+      Err(sqlx::Error::RowNotFound)
+    } else {
+      Ok(())
+    }
+  }
+}
+
+///
+/// A wrapper around `AuthenticatedUser` that requires the
+/// user to not be suspended. Use this instead of `AuthenticatedUser`
+/// for endpoints that write something on behalf of the user (bidding,
+/// offering, reacting, etc).
+///
+/// ```rs
+/// pub async fn my_handler(
+///   ActiveUser(user): ActiveUser,
+///   Json(body): Json<MyRequestBody>,
+/// ) -> Result<impl IntoResponse, MyError> {
+///   // ...
+/// }
+/// ```
+///
+pub struct ActiveUser(pub AuthenticatedUser);
+
+impl<S> FromRequestParts<S> for ActiveUser
+where
+  S: Send + Sync,
+{
+  type Rejection = Response;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let user = AuthenticatedUser::from_request_parts(parts, state)
+      .await
+      .map_err(IntoResponse::into_response)?;
+    let Extension(suspensions): Extension<Suspensions> =
+      Extension::from_request_parts(parts, state)
+        .await
+        .map_err(IntoResponse::into_response)?;
+
+    match suspensions.find_active(&user.id).await {
+      Ok(None) => Ok(ActiveUser(user)),
+      Ok(Some(_)) => Err(AuthError::Suspended.into_response()),
+      Err(err) => {
+        error!("Failed to check suspension of {}: {err:?}", user.email);
+        Err(AuthError::Unknown.into_response())
+      }
+    }
+  }
+}
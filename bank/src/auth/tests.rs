@@ -0,0 +1,153 @@
+use axum::http::{HeaderMap, HeaderValue};
+use serde_json::json;
+use sqlx::{types::Uuid, PgPool};
+
+use super::step_up::{digest, StepUpConfirmation, StepUps, STEP_UP_HEADER};
+use super::user::{AuthenticatedUser, VerificationStatus};
+
+async fn user(pool: &PgPool) -> AuthenticatedUser {
+  let id = Uuid::new_v4();
+  let email = format!("{id}@example.com");
+
+  sqlx::query!(
+    "insert into users (id, first_name, last_name, email) values ($1, 'Jane', 'Doe', $2)",
+    id,
+    email,
+  )
+  .execute(pool)
+  .await
+  .unwrap();
+
+  AuthenticatedUser {
+    id,
+    email,
+    first_name: "Jane".to_string(),
+    last_name: "Doe".to_string(),
+    verification: VerificationStatus::default(),
+    token: String::new(),
+  }
+}
+
+fn headers(confirmation: &StepUpConfirmation) -> HeaderMap {
+  let mut headers = HeaderMap::new();
+  headers.insert(
+    STEP_UP_HEADER,
+    HeaderValue::from_str(&confirmation.token.to_string()).unwrap(),
+  );
+
+  headers
+}
+
+fn operation() -> serde_json::Value {
+  json!({ "action": "wallet.send", "recipient": { "handle": "someone" }, "amount": 50 })
+}
+
+#[test]
+fn digest_does_not_depend_on_key_order() {
+  let a = json!({ "action": "bids.tip", "x": 1, "y": 2, "amount": 50 });
+  let b = json!({ "amount": 50, "y": 2, "x": 1, "action": "bids.tip" });
+
+  assert_eq!(digest(&a), digest(&b));
+}
+
+#[test]
+fn digest_differs_between_operations() {
+  let a = json!({ "action": "wallet.send", "amount": 50, "note": null });
+  let b = json!({ "action": "wallet.send", "amount": 50, "note": "hi" });
+  let c = json!({ "action": "wallet.send", "amount": 51, "note": null });
+
+  assert_ne!(digest(&a), digest(&b));
+  assert_ne!(digest(&a), digest(&c));
+}
+
+#[sqlx::test]
+async fn verifies_confirmation_of_the_same_operation(pool: PgPool) {
+  let step_ups = StepUps::new(pool.clone());
+  let jane = user(&pool).await;
+
+  let confirmation = step_ups
+    .confirm(&jane.id, &digest(&operation()))
+    .await
+    .unwrap();
+
+  assert!(step_ups
+    .verify(&headers(&confirmation), &jane, &operation())
+    .await
+    .unwrap());
+}
+
+#[sqlx::test]
+async fn rejects_missing_or_mismatching_confirmations(pool: PgPool) {
+  let step_ups = StepUps::new(pool.clone());
+  let jane = user(&pool).await;
+  let john = user(&pool).await;
+
+  let confirmation = step_ups
+    .confirm(&jane.id, &digest(&operation()))
+    .await
+    .unwrap();
+  let mut other = operation();
+  other["amount"] = json!(500);
+
+  assert!(!step_ups
+    .verify(&HeaderMap::new(), &jane, &operation())
+    .await
+    .unwrap());
+  assert!(!step_ups
+    .verify(&headers(&confirmation), &jane, &other)
+    .await
+    .unwrap());
+  assert!(!step_ups
+    .verify(&headers(&confirmation), &john, &operation())
+    .await
+    .unwrap());
+
+  // failed attempts don't use up the confirmation.
+  assert!(step_ups
+    .verify(&headers(&confirmation), &jane, &operation())
+    .await
+    .unwrap());
+}
+
+#[sqlx::test]
+async fn rejects_expired_confirmations(pool: PgPool) {
+  let step_ups = StepUps::new(pool.clone());
+  let jane = user(&pool).await;
+
+  let confirmation = step_ups
+    .confirm(&jane.id, &digest(&operation()))
+    .await
+    .unwrap();
+  sqlx::query!(
+    "update step_up_confirmations set expires_at = now() - interval '1 second' where id = $1",
+    confirmation.token,
+  )
+  .execute(&pool)
+  .await
+  .unwrap();
+
+  assert!(!step_ups
+    .verify(&headers(&confirmation), &jane, &operation())
+    .await
+    .unwrap());
+}
+
+#[sqlx::test]
+async fn confirmations_can_only_be_used_once(pool: PgPool) {
+  let step_ups = StepUps::new(pool.clone());
+  let jane = user(&pool).await;
+
+  let confirmation = step_ups
+    .confirm(&jane.id, &digest(&operation()))
+    .await
+    .unwrap();
+
+  assert!(step_ups
+    .verify(&headers(&confirmation), &jane, &operation())
+    .await
+    .unwrap());
+  assert!(!step_ups
+    .verify(&headers(&confirmation), &jane, &operation())
+    .await
+    .unwrap());
+}
@@ -1,18 +1,23 @@
 use axum::{
   extract::{Extension, Json, Path, Query},
   response::{IntoResponse, Response},
-  routing::get,
+  routing::{get, post},
   Router,
 };
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
 use sqlx::types::Uuid;
 use tower_http::cors::{Any, CorsLayer};
 
 use super::admin::AdminUser;
+use super::audit::AuditLog;
 use super::error::AuthError;
 use super::storage::AuthStorage;
+use super::suspension::{Suspension, Suspensions};
+use crate::bidding::{Bid, Withdrawals};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct UsersOptions {
   pub offset: Option<u32>,
   pub limit: Option<u32>,
@@ -21,13 +26,21 @@ pub struct UsersOptions {
 
 async fn users(
   Extension(storage): Extension<AuthStorage>,
-  Query(UsersOptions {
+  Extension(audit): Extension<AuditLog>,
+  Query(options): Query<UsersOptions>,
+  AdminUser(admin): AdminUser,
+) -> Result<Response, AuthError> {
+  audit
+    .record(&admin, "users.find", options.email.clone(), &options)
+    .await
+    .map_err(|_| AuthError::Unknown)?;
+
+  let UsersOptions {
     offset,
     limit,
     email,
-  }): Query<UsersOptions>,
-  AdminUser(_): AdminUser,
-) -> Result<Response, AuthError> {
+  } = options;
+
   if let Some(email) = email {
     Ok(
       storage
@@ -52,9 +65,15 @@ async fn users(
 
 async fn user(
   Extension(storage): Extension<AuthStorage>,
+  Extension(audit): Extension<AuditLog>,
   Path(id): Path<Uuid>,
-  AdminUser(_): AdminUser,
+  AdminUser(admin): AdminUser,
 ) -> Result<impl IntoResponse, AuthError> {
+  audit
+    .record(&admin, "users.find", Some(id.to_string()), &())
+    .await
+    .map_err(|_| AuthError::Unknown)?;
+
   let user = storage
     .find_user_by_id(id)
     .await
@@ -64,6 +83,106 @@ async fn user(
   Ok(Json(user))
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct SuspendBody {
+  pub reason: String,
+  pub until: Option<DateTime<Utc>>,
+  /// Unpublish all live tiles of the user.
+  #[serde(default)]
+  pub unpublish: bool,
+  /// Reject all pending bids of the user, refunding their offers.
+  #[serde(default)]
+  pub refund: bool,
+}
+
+#[derive(Serialize)]
+pub struct SuspendResult {
+  pub suspension: Suspension,
+  pub unpublished: Vec<Bid>,
+  pub refunded: Vec<Bid>,
+}
+
+///
+/// Suspends a user, so they can't bid, offer or react anymore
+/// until the suspension expires (or indefinitely, if no expiry is given).
+///
+/// Optionally, all live tiles of the user can be unpublished, and
+/// all of their pending bids rejected and refunded in the same step.
+/// Safe to retry if withdrawing the user fails midway.
+///
+async fn suspend(
+  Extension(suspensions): Extension<Suspensions>,
+  Extension(withdrawals): Extension<Withdrawals>,
+  Extension(audit): Extension<AuditLog>,
+  Path(id): Path<Uuid>,
+  AdminUser(admin): AdminUser,
+  Json(body): Json<SuspendBody>,
+) -> Result<impl IntoResponse, AuthError> {
+  if body.until.is_some_and(|until| until <= Utc::now()) {
+    return Err(AuthError::InvalidSuspension);
+  }
+
+  audit
+    .record(&admin, "users.suspend", Some(id.to_string()), &body)
+    .await
+    .map_err(|_| AuthError::Unknown)?;
+
+  let suspension = suspensions
+    .suspend(&id, &body.reason, body.until, &admin)
+    .await
+    .map_err(|err| match err {
+      sqlx::Error::RowNotFound => AuthError::UserNotFound,
+      _ => AuthError::Unknown,
+    })?;
+
+  let unpublished = if body.unpublish {
+    withdrawals.unpublish_all(&id).await.map_err(|err| {
+      error!("Failed to unpublish tiles of suspended user {id}: {err:?}");
+      AuthError::Unknown
+    })?
+  } else {
+    vec![]
+  };
+
+  let refunded = if body.refund {
+    withdrawals
+      .refund_all(&id, &body.reason, &admin)
+      .await
+      .map_err(|err| {
+        error!("Failed to refund bids of suspended user {id}: {err:?}");
+        AuthError::Unknown
+      })?
+  } else {
+    vec![]
+  };
+
+  Ok(Json(SuspendResult {
+    suspension,
+    unpublished,
+    refunded,
+  }))
+}
+
+///
+/// Lifts the suspension of a user.
+///
+async fn lift_suspension(
+  Extension(suspensions): Extension<Suspensions>,
+  Extension(audit): Extension<AuditLog>,
+  Path(id): Path<Uuid>,
+  AdminUser(admin): AdminUser,
+) -> Result<impl IntoResponse, AuthError> {
+  audit
+    .record(&admin, "users.lift_suspension", Some(id.to_string()), &())
+    .await
+    .map_err(|_| AuthError::Unknown)?;
+
+  suspensions.lift(&id).await.map_err(|err| match err {
+    sqlx::Error::RowNotFound => AuthError::UserNotFound,
+    _ => AuthError::Unknown,
+  })
+}
+
 pub fn router() -> Router {
   let cors = CorsLayer::new()
     .allow_methods(Any)
@@ -73,5 +192,6 @@ pub fn router() -> Router {
   Router::new()
     .route("/", get(users))
     .route("/{id}", get(user))
+    .route("/{id}/suspension", post(suspend).delete(lift_suspension))
     .layer(cors)
 }
@@ -7,7 +7,7 @@ use sqlx::types::Uuid;
 
 use super::super::book::{Bid, Book, Coords};
 use super::super::error::BiddingError;
-use crate::auth::AuthenticatedUser;
+use crate::auth::{ActiveUser, AuthenticatedUser};
 
 pub struct OwnedBidById(pub Bid, pub AuthenticatedUser);
 
@@ -27,7 +27,7 @@ where
     let Extension(book) = Extension::<Book>::from_request_parts(parts, state)
       .await
       .map_err(IntoResponse::into_response)?;
-    let bidder = AuthenticatedUser::from_request_parts(parts, state)
+    let ActiveUser(bidder) = ActiveUser::from_request_parts(parts, state)
       .await
       .map_err(IntoResponse::into_response)?;
 
@@ -60,7 +60,7 @@ where
     let Extension(book) = Extension::<Book>::from_request_parts(parts, state)
       .await
       .map_err(IntoResponse::into_response)?;
-    let bidder = AuthenticatedUser::from_request_parts(parts, state)
+    let ActiveUser(bidder) = ActiveUser::from_request_parts(parts, state)
       .await
       .map_err(IntoResponse::into_response)?;
 
@@ -0,0 +1,125 @@
+use axum::{
+  extract::{Extension, Json, Path},
+  response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use super::super::book::{BlockedRegion, Book, Coords};
+use super::super::error::BiddingError;
+use crate::auth::{admin::AdminUser, audit::AuditLog};
+
+///
+/// A blocked region, as shown to the public.
+///
+#[derive(Serialize)]
+pub struct PublicBlockedRegion {
+  pub min_x: i32,
+  pub min_y: i32,
+  pub max_x: i32,
+  pub max_y: i32,
+  pub reason: String,
+}
+
+impl From<BlockedRegion> for PublicBlockedRegion {
+  fn from(region: BlockedRegion) -> Self {
+    Self {
+      min_x: region.min_x,
+      min_y: region.min_y,
+      max_x: region.max_x,
+      max_y: region.max_y,
+      reason: region.reason,
+    }
+  }
+}
+
+///
+/// Returns all regions of the grid that currently cannot be bid on,
+/// so that clients can mark them accordingly. Admins get the full
+/// regions (e.g. who blocked them), everyone else only their bounds and reason.
+///
+pub async fn blocked_regions(
+  Extension(book): Extension<Book>,
+  admin: Option<AdminUser>,
+) -> Result<Response, BiddingError> {
+  let regions = book
+    .blocked
+    .active()
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+
+  if admin.is_some() {
+    Ok(Json(regions).into_response())
+  } else {
+    Ok(
+      Json(
+        regions
+          .into_iter()
+          .map(PublicBlockedRegion::from)
+          .collect::<Vec<_>>(),
+      )
+      .into_response(),
+    )
+  }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BlockRegionBody {
+  pub from: Coords,
+  pub to: Coords,
+  pub reason: String,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+///
+/// Blocks a rectangular region of the grid, so it can't be bid on.
+/// Already published tiles within the region are not affected.
+/// Requires admin authentication.
+///
+pub async fn block_region(
+  Extension(book): Extension<Book>,
+  Extension(audit): Extension<AuditLog>,
+  AdminUser(admin): AdminUser,
+  Json(body): Json<BlockRegionBody>,
+) -> Result<impl IntoResponse, BiddingError> {
+  audit
+    .record(
+      &admin,
+      "bids.block_region",
+      Some(format!("{}-{}", body.from, body.to)),
+      &body,
+    )
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+
+  let region = book
+    .blocked
+    .block(body.from, body.to, &body.reason, body.expires_at, &admin)
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+
+  Ok(Json(region))
+}
+
+///
+/// Unblocks a previously blocked region. Requires admin authentication.
+///
+pub async fn unblock_region(
+  Extension(book): Extension<Book>,
+  Extension(audit): Extension<AuditLog>,
+  Path(id): Path<Uuid>,
+  AdminUser(admin): AdminUser,
+) -> Result<impl IntoResponse, BiddingError> {
+  audit
+    .record(&admin, "bids.unblock_region", Some(id.to_string()), &())
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+
+  book.blocked.unblock(&id).await.map_err(|err| match err {
+    sqlx::Error::RowNotFound => BiddingError::NotFound,
+    _ => BiddingError::Unknown,
+  })?;
+
+  Ok(())
+}
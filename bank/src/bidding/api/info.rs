@@ -3,10 +3,12 @@ use axum::{
   response::IntoResponse,
 };
 use chrono::{DateTime, Utc};
+use log::error;
 use serde::{Deserialize, Serialize};
 use sqlx::types::Uuid;
 
 use crate::auth::{admin::AdminUser, AuthenticatedUser};
+use crate::profiles::Profiles;
 
 use super::super::book::{bid::next_auction_time, Bid, Book, Coords};
 use super::super::config::Config;
@@ -17,6 +19,8 @@ use super::validate::validate_coords;
 #[derive(Serialize, Debug)]
 pub struct BiddingInfo {
   pub last_bid: Option<Bid>,
+  /// The handle of the occupant, if they have a public profile.
+  pub occupant_handle: Option<String>,
   pub next_auction: Option<DateTime<Utc>>,
   pub minimum_bid: u32,
   pub own_bid: bool,
@@ -28,18 +32,28 @@ pub struct BiddingInfo {
 /// - The last winning bid on the coordinate (if any)
 /// - The next auction time (`None` means as soon as possible)
 /// - The minimum bid required to participate in the auction
+/// - The handle of the occupant, if they have a public profile
 ///
 pub async fn bidding_info(
   Extension(book): Extension<Book>,
   Extension(config): Extension<Config>,
+  Extension(profiles): Extension<Profiles>,
   Path(coords): Path<Coords>,
   user: Option<AuthenticatedUser>,
 ) -> Result<impl IntoResponse, BiddingError> {
-  validate_coords(coords, &config)?;
+  validate_coords(coords, &book).await?;
   let Ok(occupant) = book.get_occupant_bid(&coords).await else {
     return Err(BiddingError::Unknown);
   };
 
+  let occupant_handle = match &occupant {
+    Some(bid) => profiles
+      .public_handle_of(&bid.bidder)
+      .await
+      .map_err(|_| BiddingError::Unknown)?,
+    None => None,
+  };
+
   // TODO: `last_bid` isn't necessarily the current occupant,
   //        the column should be separated and used as such.
 
@@ -50,6 +64,7 @@ pub async fn bidding_info(
     },
     next_auction: next_auction_time(occupant.as_ref(), &config),
     last_bid: occupant,
+    occupant_handle,
     minimum_bid: config.minimum_bid,
   }))
 }
@@ -86,3 +101,35 @@ pub async fn all_live_bids(
     .map_err(|_| BiddingError::Unknown)
     .map(Json)
 }
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+///
+/// Returns every bid that was ever published on given coordinates,
+/// oldest first, alongside the window in which it occupied the tile.
+/// Content and rejection details of rejected bids are only returned to admins.
+///
+pub async fn tile_history(
+  Extension(book): Extension<Book>,
+  Path(coords): Path<Coords>,
+  Query(HistoryQuery { offset, limit }): Query<HistoryQuery>,
+  admin: Option<AdminUser>,
+) -> Result<impl IntoResponse, BiddingError> {
+  book
+    .get_tile_history(
+      &coords,
+      admin.is_some(),
+      offset.unwrap_or(0),
+      limit.unwrap_or(32),
+    )
+    .await
+    .map_err(|err| {
+      error!("Failed to fetch history of {coords}: {err:?}");
+      BiddingError::Unknown
+    })
+    .map(Json)
+}
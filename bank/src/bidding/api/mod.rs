@@ -1,5 +1,6 @@
 mod admin;
 mod auth;
+mod blocked;
 mod info;
 mod post_bid;
 mod publish;
@@ -7,7 +8,8 @@ mod suggest;
 mod user_bids;
 mod validate;
 
-pub use info::{all_live_bids, bidding_info, occupant_bid};
+pub use blocked::{block_region, blocked_regions, unblock_region};
+pub use info::{all_live_bids, bidding_info, occupant_bid, tile_history};
 pub use post_bid::{init_bid, post_bid, rescind_bid};
 pub use publish::{publish, reject, unpublish};
 pub use suggest::suggest;
@@ -16,8 +16,8 @@ use super::super::upload;
 use super::auth::OwnedBidById;
 use super::publish::publish;
 use super::validate::{validate_content, validate_tx};
-use crate::auth::AuthenticatedUser;
-use crate::wallet::{auth::UsableOutgoingOffer, Ledger};
+use crate::auth::ActiveUser;
+use crate::wallet::{auth::UsableOutgoingOffer, error::WalletError, Ledger};
 
 #[derive(Serialize)]
 pub struct InitResponse {
@@ -95,12 +95,15 @@ pub struct PostBidBody {
 /// will be published immediately. Otherwise, it will be published at the next auction time
 /// (if no higher bids are placed).
 ///
+/// The bid counts against the spending limits of the bidder (see `Ledger::check_spending()`),
+/// alongside whatever else they have spent today.
+///
 pub async fn post_bid(
   Extension(book): Extension<Book>,
   Extension(ledger): Extension<Ledger>,
   Extension(publisher): Extension<Publisher>,
   Extension(config): Extension<Config>,
-  bidder: AuthenticatedUser,
+  ActiveUser(bidder): ActiveUser,
   Path(coords): Path<Coords>,
   Json(body): Json<PostBidBody>,
 ) -> Result<impl IntoResponse, BiddingError> {
@@ -110,6 +113,14 @@ pub async fn post_bid(
     .map_err(|_| BiddingError::IncorrectTransaction)?;
   validate_tx(&book, &tx, &bidder, coords, &config).await?;
 
+  ledger
+    .check_spending(&bidder.id, tx.total(), tx.id.as_ref())
+    .await
+    .map_err(|err| match err {
+      WalletError::LimitExceeded { resets_at } => BiddingError::LimitExceeded(resets_at),
+      _ => BiddingError::Unknown,
+    })?;
+
   let content = BidContent {
     title: Some(body.title),
     image: Some(body.image),
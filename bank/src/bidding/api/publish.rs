@@ -2,14 +2,14 @@ use axum::{
   extract::{Extension, Json},
   response::IntoResponse,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::super::book::{Bid, Book};
 use super::super::error::BiddingError;
 use super::super::publisher::Publisher;
 use super::admin::BidByIdForAdmin;
 use super::auth::OwnedLiveBidByCoords;
-use crate::auth::admin::AdminUser;
+use crate::auth::{admin::AdminUser, audit::AuditLog};
 use crate::commit_tx;
 use crate::wallet::{Ledger, Transaction};
 
@@ -64,7 +64,7 @@ pub async fn unpublish(
   Ok(())
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct RejectBody {
   pub reason: String,
 }
@@ -72,6 +72,7 @@ pub struct RejectBody {
 pub async fn reject(
   Extension(book): Extension<Book>,
   Extension(publisher): Extension<Publisher>,
+  Extension(audit): Extension<AuditLog>,
   BidByIdForAdmin(mut bid, AdminUser(user)): BidByIdForAdmin,
   Json(body): Json<RejectBody>,
 ) -> Result<impl IntoResponse, BiddingError> {
@@ -85,6 +86,11 @@ pub async fn reject(
     .await
     .map_err(|_| BiddingError::Unknown)?;
 
+  audit
+    .record(&user, "bids.reject", Some(bid.id.to_string()), &body)
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+
   book
     .reject(&mut bid, &user, &body.reason)
     .await
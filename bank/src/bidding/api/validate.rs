@@ -8,13 +8,14 @@ use crate::wallet::Transaction;
 
 ///
 /// Checks if given coordinates are ok to bid on.
-/// Some coordinates may be blocked for system tiles or other reasons.
+/// Some regions may be blocked for system tiles or other reasons.
 ///
-pub fn validate_coords(coords: Coords, config: &Config) -> Result<(), BiddingError> {
-  if config.blocked_coords.iter().any(|&c| c == coords) {
-    return Err(BiddingError::UnauthorizedCoords);
+pub async fn validate_coords(coords: Coords, book: &Book) -> Result<(), BiddingError> {
+  match book.blocked.find(coords).await {
+    Ok(None) => Ok(()),
+    Ok(Some(_)) => Err(BiddingError::UnauthorizedCoords),
+    Err(_) => Err(BiddingError::Unknown),
   }
-  Ok(())
 }
 
 ///
@@ -33,7 +34,7 @@ pub async fn validate_tx(
   coords: Coords,
   config: &Config,
 ) -> Result<(), BiddingError> {
-  validate_coords(coords, config)?;
+  validate_coords(coords, book).await?;
 
   if !transaction.is_usable_offer_from(&bidder.id) {
     return Err(BiddingError::UnauthorizedTransaction);
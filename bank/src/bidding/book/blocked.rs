@@ -0,0 +1,187 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+use tokio::sync::RwLock;
+
+use super::coords::Coords;
+use crate::auth::AuthenticatedUser;
+
+///
+/// How long the in-memory copy of blocked regions is trusted
+/// before being reloaded from the database. Changes made through this
+/// instance are reflected immediately, this interval only matters for
+/// changes made by other instances.
+///
+const REFRESH_INTERVAL: Duration = Duration::from_mins(1);
+
+///
+/// A rectangular region of the grid that cannot be bid on.
+/// Both corners are included in the region.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockedRegion {
+  pub id: Uuid,
+  pub min_x: i32,
+  pub min_y: i32,
+  pub max_x: i32,
+  pub max_y: i32,
+  pub reason: String,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub created_by: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl BlockedRegion {
+  pub fn contains(&self, coords: Coords) -> bool {
+    (self.min_x..=self.max_x).contains(&coords.x) && (self.min_y..=self.max_y).contains(&coords.y)
+  }
+
+  pub fn is_expired(&self) -> bool {
+    self.expires_at.is_some_and(|at| at <= Utc::now())
+  }
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+  regions: Vec<BlockedRegion>,
+  loaded_at: Option<Instant>,
+}
+
+///
+/// Keeps track of blocked regions of the grid. Regions are stored
+/// in the database, and cached in memory since they are checked
+/// on every bid.
+///
+#[derive(Debug, Clone)]
+pub struct BlockedRegions {
+  pool: Pool<Postgres>,
+  cache: Arc<RwLock<Cache>>,
+}
+
+impl BlockedRegions {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self {
+      pool,
+      cache: Arc::new(RwLock::new(Cache::default())),
+    }
+  }
+
+  async fn refresh(&self) -> Result<(), sqlx::Error> {
+    let regions = sqlx::query_as!(
+      BlockedRegion,
+      "
+        select * from blocked_regions
+        where expires_at is null or expires_at > now()
+        order by created_at
+      "
+    )
+    .fetch_all(&self.pool)
+    .await?;
+
+    let mut cache = self.cache.write().await;
+    cache.regions = regions;
+    cache.loaded_at = Some(Instant::now());
+
+    Ok(())
+  }
+
+  ///
+  /// Returns all regions that are currently blocked.
+  ///
+  pub async fn active(&self) -> Result<Vec<BlockedRegion>, sqlx::Error> {
+    let stale = self
+      .cache
+      .read()
+      .await
+      .loaded_at
+      .is_none_or(|at| at.elapsed() > REFRESH_INTERVAL);
+
+    if stale {
+      self.refresh().await?;
+    }
+
+    Ok(
+      self
+        .cache
+        .read()
+        .await
+        .regions
+        .iter()
+        .filter(|region| !region.is_expired())
+        .cloned()
+        .collect(),
+    )
+  }
+
+  ///
+  /// Returns the region blocking given coordinates, if any.
+  ///
+  pub async fn find(&self, coords: Coords) -> Result<Option<BlockedRegion>, sqlx::Error> {
+    Ok(
+      self
+        .active()
+        .await?
+        .into_iter()
+        .find(|region| region.contains(coords)),
+    )
+  }
+
+  ///
+  /// Blocks the rectangular region between given corners.
+  ///
+  /// ### Params:
+  /// - `from`, `to`: opposite corners of the region (both included)
+  /// - `reason`: why the region is blocked
+  /// - `expires_at`: when the region should be unblocked, `None` to keep it blocked indefinitely
+  /// - `admin`: the admin blocking the region
+  ///
+  pub async fn block(
+    &self,
+    from: Coords,
+    to: Coords,
+    reason: &str,
+    expires_at: Option<DateTime<Utc>>,
+    admin: &AuthenticatedUser,
+  ) -> Result<BlockedRegion, sqlx::Error> {
+    let region = sqlx::query_as!(
+      BlockedRegion,
+      "
+        insert into blocked_regions (min_x, min_y, max_x, max_y, reason, expires_at, created_by)
+        values ($1, $2, $3, $4, $5, $6, $7)
+        returning *
+      ",
+      from.x.min(to.x),
+      from.y.min(to.y),
+      from.x.max(to.x),
+      from.y.max(to.y),
+      reason,
+      expires_at,
+      admin.id
+    )
+    .fetch_one(&self.pool)
+    .await?;
+
+    self.refresh().await?;
+    Ok(region)
+  }
+
+  ///
+  /// Unblocks given region. Returns `RowNotFound` if no such region exists.
+  ///
+  pub async fn unblock(&self, id: &Uuid) -> Result<(), sqlx::Error> {
+    let res = sqlx::query!("delete from blocked_regions where id = $1", id)
+      .execute(&self.pool)
+      .await?;
+
+    self.refresh().await?;
+
+    if res.rows_affected() == 0 {
+      Err(sqlx::Error::RowNotFound)
+    } else {
+      Ok(())
+    }
+  }
+}
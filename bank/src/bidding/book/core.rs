@@ -3,6 +3,7 @@ use sqlx::{postgres::Postgres, Pool, types::Uuid};
 
 use super::super::config::Config;
 use super::bid::{Bid, BidContent};
+use super::blocked::BlockedRegions;
 use super::coords::Coords;
 use crate::wallet::Transaction;
 use crate::auth::AuthenticatedUser;
@@ -11,11 +12,17 @@ use crate::auth::AuthenticatedUser;
 pub struct Book {
   pub pool: Pool<Postgres>,
   pub config: Config,
+  pub blocked: BlockedRegions,
 }
 
 impl Book {
   pub fn new(config: Config, pool: Pool<Postgres>) -> Self {
-    Self { pool, config }
+    let blocked = BlockedRegions::new(pool.clone());
+    Self {
+      pool,
+      config,
+      blocked,
+    }
   }
 
   pub async fn record_bid(
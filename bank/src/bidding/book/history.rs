@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::types::Json;
+
+use super::super::reactions::{ReactionSummary, ReactionType};
+use super::bid::{Bid, BidContent};
+use super::coords::Coords;
+use super::core::Book;
+
+///
+/// A bid that once occupied (or still occupies) a tile. `ended_at` is `None`
+/// while the bid is live, and might also be `None` for bids that were
+/// unpublished before occupancy windows were tracked.
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct Occupancy {
+  pub bid: Bid,
+  pub started_at: DateTime<Utc>,
+  pub ended_at: Option<DateTime<Utc>>,
+  pub live: bool,
+  pub rejected: bool,
+  pub reactions: ReactionSummary,
+}
+
+impl Book {
+  ///
+  /// Returns bids that were ever published on given tile, in the order
+  /// they were published. Unless `include_rejected` is set, the content of
+  /// rejected bids is omitted, as they were rejected for their content, and
+  /// so are the details of their rejection (only the `rejected` flag is kept).
+  ///
+  pub async fn get_tile_history(
+    &self,
+    coords: &Coords,
+    include_rejected: bool,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Occupancy>, sqlx::Error> {
+    let rows = sqlx::query!(
+      r#"
+        select
+          bids.id, bids.bidder, bids.tx, bids.x, bids.y, bids.content, bids.amount,
+          bids.created_at, bids.published_at, bids.rejection,
+          occupancy.started_at, occupancy.ended_at,
+          (tile.occupant_bid is not null) as "live!",
+          coalesce(
+            (
+              select jsonb_object_agg(rs.reaction, rs.count) from reaction_summary rs
+              where rs.bid_id = bids.id and rs.count > 0
+            ),
+            '{}'::jsonb
+          ) as "reactions!: Json<BTreeMap<ReactionType, i32>>"
+        from tile_occupancies occupancy
+        join bids on bids.id = occupancy.bid_id
+        left join published_tiles tile on tile.occupant_bid = bids.id
+        where occupancy.x = $1 and occupancy.y = $2
+        order by occupancy.started_at
+        offset $3 limit $4
+      "#,
+      coords.x,
+      coords.y,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| Occupancy {
+          rejected: row.rejection.is_some(),
+          bid: Bid {
+            id: row.id,
+            bidder: row.bidder,
+            tx: row.tx,
+            x: row.x,
+            y: row.y,
+            content: if row.rejection.is_none() || include_rejected {
+              BidContent::from(row.content)
+            } else {
+              BidContent::from(serde_json::Value::Null)
+            },
+            amount: row.amount,
+            created_at: row.created_at,
+            published_at: row.published_at,
+            rejection: if include_rejected {
+              row.rejection
+            } else {
+              None
+            },
+          },
+          started_at: row.started_at,
+          ended_at: row.ended_at,
+          live: row.live,
+          reactions: ReactionSummary {
+            counts: row.reactions.0,
+            viewer_reaction: None,
+          },
+        })
+        .collect(),
+    )
+  }
+}
@@ -126,6 +126,12 @@ impl Book {
                 and tile.occupant_bid is not null
                 and tile.last_published_at > now() - $1::interval
             )
+            and not exists (
+              select 1 from blocked_regions region
+              where bid.x between region.min_x and region.max_x
+                and bid.y between region.min_y and region.max_y
+                and (region.expires_at is null or region.expires_at > now())
+            )
         )
         select distinct on (bid.x, bid.y)
           bid.id, bid.bidder, bid.tx, bid.x, bid.y, bid.content, bid.amount,
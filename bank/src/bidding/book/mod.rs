@@ -1,5 +1,7 @@
+mod blocked;
 mod coords;
 mod core;
+mod history;
 mod publish;
 mod info;
 mod user;
@@ -7,4 +9,5 @@ pub mod bid;
 
 pub use core::Book;
 pub use bid::{ Bid, BidContent };
-pub use coords::Coords;
\ No newline at end of file
+pub use coords::Coords;
+pub use blocked::BlockedRegion;
\ No newline at end of file
@@ -31,12 +31,39 @@ impl Book {
     .execute(&mut *tx)
     .await?;
 
+    // the previous occupant (if any) is replaced by this bid
+    sqlx::query!(
+      "
+        update tile_occupancies set ended_at = now()
+        where x = $1 and y = $2 and ended_at is null and bid_id <> $3
+      ",
+      bid.x,
+      bid.y,
+      bid.id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+      "
+        insert into tile_occupancies (bid_id, x, y) values ($1, $2, $3)
+        on conflict (bid_id) do nothing
+      ",
+      bid.id,
+      bid.x,
+      bid.y
+    )
+    .execute(&mut *tx)
+    .await?;
+
     tx.commit().await?;
     bid.published_at = Some(Utc::now());
     Ok(())
   }
 
   pub async fn unpublish(&self, bid: &mut Bid) -> Result<(), sqlx::Error> {
+    let mut tx = self.pool.begin().await?;
+
     let res = sqlx::query!(
       "
         update published_tiles
@@ -47,13 +74,21 @@ impl Book {
       bid.y,
       bid.id
     )
-    .execute(&self.pool)
+    .execute(&mut *tx)
     .await?;
     if res.rows_affected() == 0 {
-      Err(sqlx::Error::RowNotFound)
-    } else {
-      Ok(())
+      return Err(sqlx::Error::RowNotFound);
     }
+
+    sqlx::query!(
+      "update tile_occupancies set ended_at = now() where bid_id = $1 and ended_at is null",
+      bid.id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
   }
 
   pub async fn reject(
@@ -95,6 +130,13 @@ impl Book {
     .execute(&mut *tx)
     .await?;
 
+    sqlx::query!(
+      "update tile_occupancies set ended_at = now() where bid_id = $1 and ended_at is null",
+      bid.id
+    )
+    .execute(&mut *tx)
+    .await?;
+
     tx.commit().await?;
     Ok(())
   }
@@ -129,4 +129,19 @@ impl Book {
     .await
     .context("Failed to fetch all bids for user")
   }
+
+  pub async fn get_open_bids_of(&self, user_id: &Uuid) -> Result<Vec<Bid>, anyhow::Error> {
+    sqlx::query_as!(
+      Bid,
+      "
+        select * from bids
+        where bidder = $1 and published_at is null and rejection is null
+        order by created_at desc
+      ",
+      user_id
+    )
+    .fetch_all(&self.pool)
+    .await
+    .context("Failed to fetch open bids of user")
+  }
 }
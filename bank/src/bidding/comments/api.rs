@@ -0,0 +1,202 @@
+use axum::{
+  extract::{Extension, Json, Path, Query},
+  response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use crate::auth::{admin::AdminUser, audit::AuditLog, ActiveUser, AuthenticatedUser};
+
+use super::super::book::{Bid, Book, Coords};
+use super::super::config::Config;
+use super::super::error::BiddingError;
+use super::storage::{Comment, CommentStore};
+
+async fn occupant(book: &Book, coords: &Coords) -> Result<Bid, BiddingError> {
+  match book.get_occupant_bid(coords).await {
+    Ok(Some(bid)) => Ok(bid),
+    Ok(None) => Err(BiddingError::NotFound),
+    Err(_) => Err(BiddingError::Unknown),
+  }
+}
+
+fn not_found_or_unknown(err: sqlx::Error) -> BiddingError {
+  match err {
+    sqlx::Error::RowNotFound => BiddingError::NotFound,
+    err => {
+      error!("Failed to update comment: {err:?}");
+      BiddingError::Unknown
+    }
+  }
+}
+
+#[derive(Deserialize)]
+pub struct CommentsQuery {
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct CommentsResponse {
+  pub disabled: bool,
+  pub comments: Vec<Comment>,
+}
+
+///
+/// Returns comments on the current occupant of given tile, oldest first.
+/// Replies reference the comment they reply to via `parent_id`.
+///
+pub async fn comments(
+  Extension(book): Extension<Book>,
+  Extension(comments): Extension<CommentStore>,
+  Path(coords): Path<Coords>,
+  Query(CommentsQuery { offset, limit }): Query<CommentsQuery>,
+) -> Result<impl IntoResponse, BiddingError> {
+  let bid = occupant(&book, &coords).await?;
+
+  let disabled = comments
+    .are_disabled(&bid.id)
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+  let comments = comments
+    .comments_of(&bid.id, offset.unwrap_or(0), limit.unwrap_or(32))
+    .await
+    .map_err(|err| {
+      error!("Failed to fetch comments: {err:?}");
+      BiddingError::Unknown
+    })?;
+
+  Ok(Json(CommentsResponse { disabled, comments }))
+}
+
+#[derive(Deserialize)]
+pub struct CommentRequest {
+  pub content: String,
+  pub parent_id: Option<Uuid>,
+}
+
+///
+/// Comments on the current occupant of given tile, or replies to
+/// an existing comment on it. Comments are limited in length, and users
+/// can only post a limited number of comments within a time window.
+///
+pub async fn comment(
+  Extension(book): Extension<Book>,
+  Extension(comments): Extension<CommentStore>,
+  Extension(config): Extension<Config>,
+  Path(coords): Path<Coords>,
+  ActiveUser(user): ActiveUser,
+  Json(req): Json<CommentRequest>,
+) -> Result<impl IntoResponse, BiddingError> {
+  let content = req.content.trim();
+  if content.is_empty() || content.chars().count() > config.comments.max_length {
+    return Err(BiddingError::InvalidComment);
+  }
+
+  let bid = occupant(&book, &coords).await?;
+
+  if comments
+    .are_disabled(&bid.id)
+    .await
+    .map_err(|_| BiddingError::Unknown)?
+  {
+    return Err(BiddingError::CommentsDisabled);
+  }
+
+  // windows too large to subtract from now cover all comments anyway.
+  let since = chrono::Duration::from_std(config.comments.rate_limit_window)
+    .ok()
+    .and_then(|window| Utc::now().checked_sub_signed(window))
+    .unwrap_or(DateTime::UNIX_EPOCH);
+
+  comments
+    .post(
+      &bid.id,
+      req.parent_id,
+      content,
+      &user,
+      since,
+      config.comments.rate_limit,
+    )
+    .await
+    .map_err(not_found_or_unknown)?
+    .map(Json)
+    .ok_or(BiddingError::TooManyRequests)
+}
+
+#[derive(Deserialize)]
+pub struct SettingsRequest {
+  pub disabled: bool,
+}
+
+///
+/// Turns comments on the current occupant of given tile on or off.
+/// Only the owner of the tile can do this. Existing comments are kept,
+/// but no new comments can be posted while comments are off.
+///
+pub async fn update_settings(
+  Extension(book): Extension<Book>,
+  Extension(comments): Extension<CommentStore>,
+  Path(coords): Path<Coords>,
+  ActiveUser(user): ActiveUser,
+  Json(req): Json<SettingsRequest>,
+) -> Result<impl IntoResponse, BiddingError> {
+  let bid = occupant(&book, &coords).await?;
+  if bid.bidder != user.id {
+    return Err(BiddingError::UnauthorizedCoords);
+  }
+
+  comments
+    .set_disabled(&bid.id, req.disabled)
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+
+  Ok(())
+}
+
+///
+/// Deletes a comment on a bid on given tile. Only the author of the comment
+/// can do this. The comment is looked up by its id, so comments on bids that
+/// no longer occupy the tile can still be deleted.
+///
+pub async fn delete_comment(
+  Extension(comments): Extension<CommentStore>,
+  Path((_, id)): Path<(Coords, Uuid)>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, BiddingError> {
+  comments
+    .delete(&id, &user)
+    .await
+    .map_err(not_found_or_unknown)
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RemoveBody {
+  pub reason: String,
+}
+
+///
+/// Removes a comment on a bid on given tile, looking it up by its id
+/// (similar to `delete_comment()`). Requires admin authentication.
+///
+pub async fn remove_comment(
+  Extension(comments): Extension<CommentStore>,
+  Extension(audit): Extension<AuditLog>,
+  Path((_, id)): Path<(Coords, Uuid)>,
+  AdminUser(admin): AdminUser,
+  Json(body): Json<RemoveBody>,
+) -> Result<impl IntoResponse, BiddingError> {
+  audit
+    .record(&admin, "comments.remove", Some(id.to_string()), &body)
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+
+  comments
+    .remove(&id, &body.reason, &admin)
+    .await
+    .map_err(not_found_or_unknown)?;
+
+  Ok(())
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use axum::{
+  extract::Extension,
+  routing::{delete, get, post, put},
+  Router,
+};
+use serde::Deserialize;
+use sqlx::{postgres::Postgres, Pool};
+use storage::CommentStore;
+
+mod api;
+mod storage;
+
+///
+/// Configuration for comments on tiles. Includes the following:
+/// - The maximum length of a comment (in characters),
+/// - How many comments a user can post within the rate limit window.
+///
+/// ### Example (TOML):
+/// ```toml
+/// max_length = 280
+/// rate_limit = 5
+/// rate_limit_window = "1min"
+/// ```
+///
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+  pub max_length: usize,
+  pub rate_limit: u32,
+  #[serde(with = "humantime_serde")]
+  pub rate_limit_window: Duration,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self {
+      max_length: 280,
+      rate_limit: 5,
+      rate_limit_window: Duration::from_mins(1),
+    }
+  }
+}
+
+pub fn router(db: &Pool<Postgres>) -> Router {
+  let comments = CommentStore::new(db.clone());
+
+  Router::new()
+    .route("/", get(api::comments))
+    .route("/", post(api::comment))
+    .route("/settings", put(api::update_settings)) // --> owner turns comments on or off
+    .route("/{id}", delete(api::delete_comment)) // --> author deletes their comment
+    .route("/{id}/remove", delete(api::remove_comment)) // --> admin removes a comment
+    .layer(Extension(comments))
+}
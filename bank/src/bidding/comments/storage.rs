@@ -0,0 +1,234 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+
+use crate::auth::AuthenticatedUser;
+
+///
+/// A comment left on a bid. Deleted (or removed) comments are
+/// still returned, so replies to them keep their context, but
+/// their content is omitted.
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct Comment {
+  pub id: Uuid,
+  pub bid_id: Uuid,
+  pub parent_id: Option<Uuid>,
+  pub author: Uuid,
+  pub content: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct CommentStore {
+  pool: Pool<Postgres>,
+}
+
+impl CommentStore {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  pub async fn comments_of(
+    &self,
+    bid_id: &Uuid,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Comment>, sqlx::Error> {
+    sqlx::query_as!(
+      Comment,
+      "
+        select
+          id, bid_id, parent_id, author,
+          case when deleted_at is null then content end as content,
+          created_at, deleted_at
+        from comments
+        where bid_id = $1
+        order by created_at
+        offset $2 limit $3
+      ",
+      bid_id,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await
+  }
+
+  ///
+  /// Adds a comment to given bid, unless its author has reached the rate limit.
+  /// The author is locked while posting, and the rate limit is checked in the same
+  /// statement that adds the comment, so concurrent requests can't exceed the limit.
+  /// The lock doesn't block other rows referencing the author (e.g. their bids).
+  ///
+  /// ### Params:
+  /// - `bid_id`: the bid to comment on
+  /// - `parent_id`: the comment this comment replies to, if any
+  /// - `content`: the content of the comment
+  /// - `user`: the author of the comment
+  /// - `since`: the start of the rate limit window
+  /// - `limit`: how many comments the author can post within the window
+  ///
+  /// ### Returns:
+  /// The comment, `None` if the author has reached the rate limit, or `RowNotFound`
+  /// if the parent comment is not a (non-deleted) comment on the same bid.
+  ///
+  pub async fn post(
+    &self,
+    bid_id: &Uuid,
+    parent_id: Option<Uuid>,
+    content: &str,
+    user: &AuthenticatedUser,
+    since: DateTime<Utc>,
+    limit: u32,
+  ) -> Result<Option<Comment>, sqlx::Error> {
+    let mut db = self.pool.begin().await?;
+
+    sqlx::query!(
+      "select id from users where id = $1 for no key update",
+      user.id
+    )
+    .fetch_one(&mut *db)
+    .await?;
+
+    let row = sqlx::query!(
+      r#"
+        with recent as (
+          select count(*) as count from comments
+          where author = $3 and created_at > $5
+        ),
+        inserted as (
+          insert into comments (bid_id, parent_id, author, content)
+          select $1, $2, $3, $4 from recent
+          where recent.count < $6 and ($2::uuid is null or exists (
+            select 1 from comments parent
+            where parent.id = $2 and parent.bid_id = $1 and parent.deleted_at is null
+          ))
+          returning id, bid_id, parent_id, author, content, created_at, deleted_at
+        )
+        select
+          recent.count >= $6 as "limited!",
+          inserted.id as "id?",
+          inserted.bid_id as "bid_id?",
+          inserted.parent_id,
+          inserted.author as "author?",
+          inserted.content as "content?",
+          inserted.created_at as "created_at?",
+          inserted.deleted_at
+        from recent left join inserted on true
+      "#,
+      bid_id,
+      parent_id,
+      user.id,
+      content,
+      since,
+      i64::from(limit),
+    )
+    .fetch_one(&mut *db)
+    .await?;
+
+    db.commit().await?;
+
+    if row.limited {
+      return Ok(None);
+    }
+
+    match (row.id, row.bid_id, row.author, row.created_at) {
+      (Some(id), Some(bid_id), Some(author), Some(created_at)) => Ok(Some(Comment {
+        id,
+        bid_id,
+        parent_id: row.parent_id,
+        author,
+        content: row.content,
+        created_at,
+        deleted_at: row.deleted_at,
+      })),
+      _ => Err(sqlx::Error::RowNotFound),
+    }
+  }
+
+  ///
+  /// Deletes a comment on behalf of its author. Returns `RowNotFound`
+  /// if the comment doesn't exist, is already deleted, or is not
+  /// authored by given user.
+  ///
+  pub async fn delete(&self, id: &Uuid, user: &AuthenticatedUser) -> Result<(), sqlx::Error> {
+    let res = sqlx::query!(
+      "
+        update comments set deleted_at = now()
+        where id = $1 and author = $2 and deleted_at is null
+      ",
+      id,
+      user.id,
+    )
+    .execute(&self.pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+      Err(sqlx::Error::RowNotFound)
+    } else {
+      Ok(())
+    }
+  }
+
+  ///
+  /// Removes a comment on behalf of an admin. Returns `RowNotFound`
+  /// if the comment doesn't exist or is already deleted.
+  ///
+  pub async fn remove(
+    &self,
+    id: &Uuid,
+    reason: &str,
+    admin: &AuthenticatedUser,
+  ) -> Result<(), sqlx::Error> {
+    let res = sqlx::query!(
+      "
+        update comments set
+          deleted_at = now(),
+          removed_by = $2,
+          removal_reason = $3
+        where id = $1 and deleted_at is null
+      ",
+      id,
+      admin.id,
+      reason,
+    )
+    .execute(&self.pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+      Err(sqlx::Error::RowNotFound)
+    } else {
+      Ok(())
+    }
+  }
+
+  pub async fn are_disabled(&self, bid_id: &Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+      r#"
+        select exists (select 1 from comments_disabled where bid_id = $1) as "disabled!"
+      "#,
+      bid_id,
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+
+  pub async fn set_disabled(&self, bid_id: &Uuid, disabled: bool) -> Result<(), sqlx::Error> {
+    if disabled {
+      sqlx::query!(
+        "insert into comments_disabled (bid_id) values ($1) on conflict do nothing",
+        bid_id,
+      )
+      .execute(&self.pool)
+      .await?;
+    } else {
+      sqlx::query!("delete from comments_disabled where bid_id = $1", bid_id)
+        .execute(&self.pool)
+        .await?;
+    }
+
+    Ok(())
+  }
+}
@@ -2,21 +2,29 @@ use std::time::Duration;
 
 use serde::Deserialize;
 
-use super::book::Coords;
+use super::comments;
+use super::reactions;
 use super::upload;
 
 ///
 /// Configuration for the bidding system. Includes the following:
 /// - The guaranteed occupancy time for a tile (if a bid wins a tile, will stay on it at least for this long)
 /// - The minimum bid required for a tile,
-/// - A list of coordinates that cannot be bid on (system tiles, for example),
+/// - The reactions users can leave on published tiles, and how often they can react,
+/// - The configuration for comments on published tiles,
 /// - The configuration for image upload
+///
+/// Regions that cannot be bid on (system tiles, for example) are not part of
+/// the configuration, and are instead managed by admins at runtime.
 /// 
 /// ### Example (TOML):
 /// ```toml
 /// guaranteed_occupancy = "1h"
 /// minimum_bid = 1000
-/// blocked_coords = ["0:0", "1:1"]
+/// reactions = ["like", "fire"]
+///
+/// [comments]
+/// max_length = 280
 /// ```
 ///
 #[derive(Clone, Debug, Deserialize)]
@@ -31,8 +39,19 @@ pub struct Config {
   pub guaranteed_occupancy: Duration,
   /// The minimum bid required for a tile.
   pub minimum_bid: u32,
-  /// A list of coordinates that cannot be bid on (system tiles, for example).
-  pub blocked_coords: Vec<Coords>,
+  /// The reactions users can leave on published tiles (defaults to `like`).
+  #[serde(default = "default_reactions")]
+  pub reactions: Vec<String>,
+  /// Limits on how often users can react (defaults apply if not provided)
+  #[serde(default)]
+  pub reaction_limits: reactions::Limits,
+  /// Configuration for comments (defaults apply if not provided)
+  #[serde(default)]
+  pub comments: comments::Config,
   /// Configuration for image upload
   pub image_upload: upload::Config,
 }
+
+fn default_reactions() -> Vec<String> {
+  vec!["like".to_string()]
+}
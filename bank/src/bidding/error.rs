@@ -2,6 +2,7 @@ use axum::{
   http::StatusCode,
   response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use std::fmt::{self, Display};
 use thiserror::Error;
@@ -79,6 +80,22 @@ pub enum BiddingError {
   UnauthorizedCoords,
   #[error("Not Found")]
   NotFound,
+  #[error("Unsupported reaction")]
+  UnsupportedReaction,
+  #[error("Invalid comment")]
+  InvalidComment,
+  #[error("Comments disabled")]
+  CommentsDisabled,
+  #[error("Too many requests")]
+  TooManyRequests,
+  #[error("Email not verified")]
+  EmailNotVerified,
+  #[error("Cannot tip own tile")]
+  OwnTile,
+  #[error("Limit exceeded")]
+  LimitExceeded(Option<DateTime<Utc>>),
+  #[error("Step-up confirmation required")]
+  StepUpRequired,
 }
 
 impl IntoResponse for BiddingError {
@@ -110,6 +127,29 @@ impl IntoResponse for BiddingError {
         "Unauthorized coordinates".to_string(),
       ),
       BiddingError::NotFound => (StatusCode::NOT_FOUND, "Not Found".to_string()),
+      BiddingError::UnsupportedReaction => (
+        StatusCode::BAD_REQUEST,
+        "Unsupported reaction".to_string(),
+      ),
+      BiddingError::InvalidComment => (StatusCode::BAD_REQUEST, "Invalid comment".to_string()),
+      BiddingError::CommentsDisabled => (StatusCode::FORBIDDEN, "Comments disabled".to_string()),
+      BiddingError::TooManyRequests => (
+        StatusCode::TOO_MANY_REQUESTS,
+        "Too many requests".to_string(),
+      ),
+      BiddingError::EmailNotVerified => (StatusCode::FORBIDDEN, "Email not verified".to_string()),
+      BiddingError::OwnTile => (StatusCode::BAD_REQUEST, "Cannot tip own tile".to_string()),
+      BiddingError::LimitExceeded(resets_at) => (
+        StatusCode::FORBIDDEN,
+        resets_at.map_or_else(
+          || "Limit exceeded".to_string(),
+          |at| format!("Limit exceeded, resets at {}", at.to_rfc3339()),
+        ),
+      ),
+      BiddingError::StepUpRequired => (
+        StatusCode::FORBIDDEN,
+        "Step-up confirmation required".to_string(),
+      ),
     })
     .into_response()
   }
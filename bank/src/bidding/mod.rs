@@ -17,13 +17,20 @@ use super::wallet::Ledger;
 mod api;
 pub mod auctions;
 mod book;
+mod comments;
 pub mod config;
 pub mod error;
 mod link_preview;
 mod publisher;
 mod reactions;
 mod tile;
+mod tips;
 mod upload;
+mod withdrawals;
+
+pub use book::Bid;
+pub use reactions::Trending;
+pub use withdrawals::Withdrawals;
 
 pub fn router(config: config::Config, ledger: &Ledger, db: &Pool<Postgres>) -> Router {
   let cors = CorsLayer::new()
@@ -52,15 +59,23 @@ pub fn router(config: config::Config, ledger: &Ledger, db: &Pool<Postgres>) -> R
     .route("/suggest", get(api::suggest))
     .route("/live", get(api::live_bids))
     .route("/history", get(api::all_bids))
+    .route("/blocked", get(api::blocked_regions))
+    .route("/blocked", post(api::block_region)) // --> admin blocks a region
+    .route("/blocked/{id}", delete(api::unblock_region)) // --> admin unblocks a region
     .route("/{coords}", get(api::bidding_info))
     .route("/{coords}/occupant", get(api::occupant_bid))
+    .route("/{coords}/history", get(api::tile_history)) // --> all bids ever published on the tile
     .route("/{coords}/init", post(api::init_bid))
     .route("/{coords}", post(api::post_bid))
     .route("/{coords}", delete(api::unpublish)) // --> unpublish a published bid
     .route("/{id}/rescind", delete(api::rescind_bid)) // --> rescind bid by id, if unpublished
     .route("/{id}/reject", delete(api::reject)) // --> admin rejects a bid by id, unpublish if need be
     .route("/all/live", get(api::all_live_bids)) // --> recently published bids
-    .nest("/{coords}/reactions", reactions::router(db))
+    .nest("/{coords}/reactions", reactions::router(db, &config.reaction_limits))
+    .nest("/{coords}/comments", comments::router(db))
+    .nest("/{coords}/tip", tips::router(db))
+    .nest("/top", reactions::leaderboard(db))
+    .nest("/trending", reactions::trending(db))
     .layer(Extension(ledger))
     .layer(Extension(book))
     .layer(Extension(publisher))
@@ -1,36 +1,55 @@
 use axum::{
-  extract::{Extension, Json, Path},
+  extract::{Extension, Json, Path, Query},
   response::IntoResponse,
 };
 use log::error;
 use serde::Deserialize;
 
-use crate::auth::AuthenticatedUser;
+use crate::auth::{ActiveUser, AuthenticatedUser};
 
 use super::super::book::{Book, Coords};
+use super::super::config::Config;
 use super::super::error::BiddingError;
-use super::storage::{ReactionStore, ReactionType};
+use super::limits::{ClientIp, ReactionLimiter};
+use super::storage::{Leaderboard, ReactionStore, ReactionType};
+use super::trending::Trending;
 
 #[derive(Deserialize)]
 pub struct ReactionRequest {
   pub reaction: ReactionType,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn react(
   Extension(book): Extension<Book>,
   Extension(reactions): Extension<ReactionStore>,
+  Extension(limiter): Extension<ReactionLimiter>,
+  Extension(config): Extension<Config>,
   Path(coords): Path<Coords>,
-  user: AuthenticatedUser,
+  ClientIp(ip): ClientIp,
+  ActiveUser(user): ActiveUser,
   Json(req): Json<ReactionRequest>,
 ) -> Result<(), BiddingError> {
+  if !req.reaction.is_allowed(&config.reactions) {
+    return Err(BiddingError::UnsupportedReaction);
+  }
+
   let occupant_bid = match book.get_occupant_bid(&coords).await {
     Ok(Some(bid)) => bid,
     Ok(None) => return Err(BiddingError::NotFound),
     Err(_) => return Err(BiddingError::Unknown),
   };
 
+  // only replacing an existing reaction with another one counts as a flip.
+  let current = reactions
+    .reaction_of(&occupant_bid.id, &user)
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+  let flips = current.is_some_and(|current| current != req.reaction);
+  limiter.check(&user, ip, flips.then_some(&occupant_bid.id))?;
+
   reactions
-    .set_reaction(&occupant_bid.id, req.reaction, &user)
+    .set_reaction(&occupant_bid.id, &req.reaction, &user)
     .await
     .map_err(|_| BiddingError::Unknown)?;
 
@@ -40,8 +59,10 @@ pub async fn react(
 pub async fn unreact(
   Extension(book): Extension<Book>,
   Extension(reactions): Extension<ReactionStore>,
+  Extension(limiter): Extension<ReactionLimiter>,
   Path(coords): Path<Coords>,
-  user: AuthenticatedUser,
+  ClientIp(ip): ClientIp,
+  ActiveUser(user): ActiveUser,
   Json(req): Json<ReactionRequest>,
 ) -> Result<(), BiddingError> {
   let occupant_bid = match book.get_occupant_bid(&coords).await {
@@ -50,8 +71,16 @@ pub async fn unreact(
     Err(_) => return Err(BiddingError::Unknown),
   };
 
+  // only removing an existing reaction counts as a flip.
+  let current = reactions
+    .reaction_of(&occupant_bid.id, &user)
+    .await
+    .map_err(|_| BiddingError::Unknown)?;
+  let flips = current.is_some_and(|current| current == req.reaction);
+  limiter.check(&user, ip, flips.then_some(&occupant_bid.id))?;
+
   reactions
-    .clear_reaction(&occupant_bid.id, req.reaction, &user)
+    .clear_reaction(&occupant_bid.id, &req.reaction, &user)
     .await
     .map_err(|err| {
       error!("Failed to clear reaction: {:?}", err);
@@ -80,3 +109,124 @@ pub async fn reactions(
 
   Ok(Json(reaction))
 }
+
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+  pub reaction: Option<ReactionType>,
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct RegionLeaderboardQuery {
+  pub from: Coords,
+  pub to: Coords,
+  pub reaction: Option<ReactionType>,
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+async fn top(
+  reactions: &ReactionStore,
+  leaderboard: &Leaderboard,
+  offset: Option<u32>,
+  limit: Option<u32>,
+) -> Result<impl IntoResponse, BiddingError> {
+  reactions
+    .top_bids(leaderboard, offset.unwrap_or(0), limit.unwrap_or(32))
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to fetch leaderboard: {err:?}");
+      BiddingError::Unknown
+    })
+}
+
+///
+/// Returns currently published bids with the most reactions.
+/// Can be narrowed down to a specific reaction type.
+///
+pub async fn top_live(
+  Extension(reactions): Extension<ReactionStore>,
+  Query(LeaderboardQuery {
+    reaction,
+    offset,
+    limit,
+  }): Query<LeaderboardQuery>,
+) -> Result<impl IntoResponse, BiddingError> {
+  let leaderboard = Leaderboard {
+    live_only: true,
+    reaction,
+    region: None,
+  };
+
+  top(&reactions, &leaderboard, offset, limit).await
+}
+
+///
+/// Returns bids with the most reactions of all time, including
+/// bids that no longer occupy their tiles (but excluding rejected bids).
+///
+pub async fn top_all_time(
+  Extension(reactions): Extension<ReactionStore>,
+  Query(LeaderboardQuery {
+    reaction,
+    offset,
+    limit,
+  }): Query<LeaderboardQuery>,
+) -> Result<impl IntoResponse, BiddingError> {
+  let leaderboard = Leaderboard {
+    live_only: false,
+    reaction,
+    region: None,
+  };
+
+  top(&reactions, &leaderboard, offset, limit).await
+}
+
+///
+/// Returns currently published bids within given rectangle
+/// with the most reactions.
+///
+pub async fn top_in_region(
+  Extension(reactions): Extension<ReactionStore>,
+  Query(RegionLeaderboardQuery {
+    from,
+    to,
+    reaction,
+    offset,
+    limit,
+  }): Query<RegionLeaderboardQuery>,
+) -> Result<impl IntoResponse, BiddingError> {
+  let leaderboard = Leaderboard {
+    live_only: true,
+    reaction,
+    region: Some((from, to)),
+  };
+
+  top(&reactions, &leaderboard, offset, limit).await
+}
+
+#[derive(Deserialize)]
+pub struct TrendingQuery {
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+///
+/// Returns live bids that are gathering reactions the fastest,
+/// with recent reactions weighing more than older ones.
+///
+pub async fn trending(
+  Extension(trending): Extension<Trending>,
+  Query(TrendingQuery { offset, limit }): Query<TrendingQuery>,
+) -> Result<impl IntoResponse, BiddingError> {
+  trending
+    .top(offset.unwrap_or(0), limit.unwrap_or(32))
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to fetch trending bids: {err:?}");
+      BiddingError::Unknown
+    })
+}
@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+  extract::{ConnectInfo, FromRequestParts},
+  http::request::Parts,
+};
+use dashmap::DashMap;
+use serde::Deserialize;
+use sqlx::types::Uuid;
+
+use super::super::error::BiddingError;
+use crate::auth::AuthenticatedUser;
+
+///
+/// Keys are only cleaned up once the number of tracked keys
+/// exceeds this, to avoid scanning the whole map on every reaction.
+///
+const CLEANUP_THRESHOLD: usize = 10_000;
+
+///
+/// Configuration for limiting how often users can react. Includes the following:
+/// - How many reactions (or removals) a user can make within the window,
+/// - How many reactions (or removals) can come from a single IP within the window,
+/// - How many times a user can change (or remove) their reaction on a single tile within the flip window,
+/// - Whether users need a verified email to react,
+/// - How many proxies in front of the server are trusted to append the client's address
+///   to `X-Forwarded-For`. When zero (default), the header is ignored.
+///
+/// ### Example (TOML):
+/// ```toml
+/// per_user = 30
+/// per_ip = 120
+/// window = "1min"
+/// max_flips = 4
+/// flip_window = "10min"
+/// require_verified_email = true
+/// trusted_proxies = 1
+/// ```
+///
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+  pub per_user: u32,
+  pub per_ip: u32,
+  #[serde(with = "humantime_serde")]
+  pub window: Duration,
+  pub max_flips: u32,
+  #[serde(with = "humantime_serde")]
+  pub flip_window: Duration,
+  pub require_verified_email: bool,
+  pub trusted_proxies: usize,
+}
+
+impl Default for Limits {
+  fn default() -> Self {
+    Self {
+      per_user: 30,
+      per_ip: 120,
+      window: Duration::from_mins(1),
+      max_flips: 4,
+      flip_window: Duration::from_mins(10),
+      require_verified_email: false,
+      trusted_proxies: 0,
+    }
+  }
+}
+
+///
+/// The IP address of the client making the request. `X-Forwarded-For` is only
+/// honoured when the server is configured to be behind trusted proxies (see
+/// `Limits::trusted_proxies`), in which case the address appended by the
+/// outermost trusted proxy is used. Otherwise, the address of the peer is used.
+///
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+  S: Send + Sync,
+{
+  type Rejection = BiddingError;
+
+  async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+    let peer = parts
+      .extensions
+      .get::<ConnectInfo<SocketAddr>>()
+      .map(|ConnectInfo(addr)| addr.ip());
+    let trusted_proxies = parts
+      .extensions
+      .get::<ReactionLimiter>()
+      .map_or(0, |limiter| limiter.limits.trusted_proxies);
+    if trusted_proxies == 0 {
+      return Ok(ClientIp(peer));
+    }
+
+    // each trusted proxy appends the address of its peer, so the address
+    // appended by the outermost one is the n-th from the end.
+    let forwarded = parts
+      .headers
+      .get("X-Forwarded-For")
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.rsplit(',').nth(trusted_proxies - 1))
+      .and_then(|ip| ip.trim().parse().ok());
+
+    Ok(ClientIp(forwarded.or(peer)))
+  }
+}
+
+///
+/// Limits how often users can react, to keep write load on reaction
+/// summaries in check. Reactions are tracked in memory (per instance),
+/// within sliding windows, as losing them is harmless.
+///
+#[derive(Clone)]
+pub struct ReactionLimiter {
+  limits: Limits,
+  hits: Arc<DashMap<String, VecDeque<Instant>>>,
+}
+
+impl ReactionLimiter {
+  pub fn new(limits: Limits) -> Self {
+    Self {
+      limits,
+      hits: Arc::new(DashMap::new()),
+    }
+  }
+
+  fn allows(&self, key: &str, max: u32, window: Duration) -> bool {
+    self.hits.get_mut(key).is_none_or(|mut hits| {
+      while hits.front().is_some_and(|at| at.elapsed() > window) {
+        hits.pop_front();
+      }
+      hits.len() < max as usize
+    })
+  }
+
+  fn record(&self, key: String) {
+    self.hits.entry(key).or_default().push_back(Instant::now());
+  }
+
+  fn cleanup(&self) {
+    if self.hits.len() > CLEANUP_THRESHOLD {
+      let window = self.limits.window.max(self.limits.flip_window);
+      self
+        .hits
+        .retain(|_, hits| hits.back().is_some_and(|at| at.elapsed() <= window));
+    }
+  }
+
+  ///
+  /// Checks whether given user can react (or remove their reaction), and if so,
+  /// records the attempt. `flip` is the bid whose existing reaction the user is
+  /// changing (or removing), if any, which is additionally limited per bid.
+  ///
+  /// ### Returns:
+  /// - `BiddingError::EmailNotVerified` if a verified email is required and the user doesn't have one,
+  /// - `BiddingError::TooManyRequests` if the user, their IP, or their reactions on the bid exceed the limits.
+  ///
+  pub fn check(
+    &self,
+    user: &AuthenticatedUser,
+    ip: Option<IpAddr>,
+    flip: Option<&Uuid>,
+  ) -> Result<(), BiddingError> {
+    if self.limits.require_verified_email && user.verification.email_verified_at.is_none() {
+      return Err(BiddingError::EmailNotVerified);
+    }
+
+    let mut keys = vec![(
+      format!("user:{}", user.id),
+      self.limits.per_user,
+      self.limits.window,
+    )];
+    if let Some(bid_id) = flip {
+      keys.push((
+        format!("flip:{}:{bid_id}", user.id),
+        self.limits.max_flips,
+        self.limits.flip_window,
+      ));
+    }
+    if let Some(ip) = ip {
+      keys.push((format!("ip:{ip}"), self.limits.per_ip, self.limits.window));
+    }
+
+    if !keys
+      .iter()
+      .all(|(key, max, window)| self.allows(key, *max, *window))
+    {
+      return Err(BiddingError::TooManyRequests);
+    }
+
+    self.cleanup();
+    for (key, _, _) in keys {
+      self.record(key);
+    }
+
+    Ok(())
+  }
+}
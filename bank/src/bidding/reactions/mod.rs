@@ -4,17 +4,52 @@ use axum::{
   Router,
 };
 use sqlx::{postgres::Postgres, Pool};
+use limits::ReactionLimiter;
 use storage::ReactionStore;
 
 mod api;
+mod limits;
 mod storage;
+mod trending;
 
-pub fn router(db: &Pool<Postgres>) -> Router {
+pub use limits::Limits;
+pub use storage::{ReactionSummary, ReactionType};
+pub use trending::Trending;
+
+pub fn router(db: &Pool<Postgres>, limits: &Limits) -> Router {
   let reactions = ReactionStore::new(db.clone());
+  let limiter = ReactionLimiter::new(limits.clone());
 
   Router::new()
     .route("/", get(api::reactions))
     .route("/", post(api::react))
     .route("/", delete(api::unreact))
     .layer(Extension(reactions))
+    .layer(Extension(limiter))
+}
+
+///
+/// Builds a router for reaction leaderboards, i.e.
+/// bids ranked by the reactions they received.
+///
+pub fn leaderboard(db: &Pool<Postgres>) -> Router {
+  let reactions = ReactionStore::new(db.clone());
+
+  Router::new()
+    .route("/", get(api::top_live))
+    .route("/all-time", get(api::top_all_time))
+    .route("/region", get(api::top_in_region))
+    .layer(Extension(reactions))
+}
+
+///
+/// Builds a router for trending bids, i.e. live bids
+/// ranked by how fast they are gathering reactions.
+///
+pub fn trending(db: &Pool<Postgres>) -> Router {
+  let trending = Trending::new(db.clone());
+
+  Router::new()
+    .route("/", get(api::trending))
+    .layer(Extension(trending))
 }
@@ -1,21 +1,59 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use sqlx::{postgres::Postgres, types::Uuid, Pool, Type};
 
+use super::super::book::{Bid, BidContent, Coords};
 use crate::auth::AuthenticatedUser;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Type, Deserialize, Serialize)]
-#[sqlx(type_name = "reaction_type", rename_all = "snake_case")]
-#[serde(rename_all = "snake_case")]
-pub enum ReactionType {
-  Like,
+///
+/// The type of a reaction, e.g. `like` or `fire`. Reaction types
+/// are configurable, so any reaction type should be checked against
+/// the configuration before being stored.
+///
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Type, Deserialize, Serialize)]
+#[sqlx(transparent)]
+#[serde(transparent)]
+pub struct ReactionType(pub String);
+
+impl ReactionType {
+  pub fn is_allowed(&self, allowed: &[String]) -> bool {
+    allowed.contains(&self.0)
+  }
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ReactionSummary {
-  pub like_count: i32,
+  pub counts: BTreeMap<ReactionType, i32>,
   pub viewer_reaction: Option<ReactionType>,
 }
 
+///
+/// A bid ranked by the reactions it received. `live` denotes
+/// whether the bid is still the occupant of its tile. `handle` is
+/// the handle of the bidder, if they have a public profile.
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+  pub bid: Bid,
+  pub handle: Option<String>,
+  pub reactions: i32,
+  pub live: bool,
+}
+
+///
+/// Describes which bids should be ranked, and how:
+/// - `live_only`: only rank bids currently occupying their tiles,
+/// - `reaction`: only count reactions of given type (all types otherwise),
+/// - `region`: only rank bids within given rectangle (corners included).
+///
+#[derive(Debug, Clone, Default)]
+pub struct Leaderboard {
+  pub live_only: bool,
+  pub reaction: Option<ReactionType>,
+  pub region: Option<(Coords, Coords)>,
+}
+
 #[derive(Clone)]
 pub struct ReactionStore {
   pool: Pool<Postgres>,
@@ -29,7 +67,7 @@ impl ReactionStore {
   pub async fn set_reaction(
     &self,
     bid_id: &Uuid,
-    reaction: ReactionType,
+    reaction: &ReactionType,
     user: &AuthenticatedUser,
   ) -> Result<(), sqlx::Error> {
     sqlx::query!(
@@ -46,24 +84,24 @@ impl ReactionStore {
             set reaction = excluded.reaction,
                 updated_at = now()
         ),
-        delta as (
-          select
-            case when (select reaction from prev) = 'like' then 1 else 0 end as prev_like,
-            case when $3 = 'like' then 1 else 0 end as new_like
+        down as (
+          update reaction_summary
+          set count = count - 1,
+              updated_at = now()
+          where bid_id = $1
+            and reaction = (select reaction from prev)
+            and reaction <> $3
         )
-        insert into reaction_summary (bid_id, like_count)
-        select
-          $1,
-          (delta.new_like - delta.prev_like)
-        from delta
-        on conflict (bid_id) do update
-        set like_count = reaction_summary.like_count
-                      + (excluded.like_count),
+        insert into reaction_summary (bid_id, reaction, count)
+        select $1, $3, 1
+        where not exists (select 1 from prev where reaction = $3)
+        on conflict (bid_id, reaction) do update
+        set count = reaction_summary.count + 1,
             updated_at = now();
       ",
       bid_id,
       user.id,
-      reaction as ReactionType,
+      reaction as &ReactionType,
     )
     .execute(&self.pool)
     .await?;
@@ -74,7 +112,7 @@ impl ReactionStore {
   pub async fn clear_reaction(
     &self,
     bid_id: &Uuid,
-    reaction: ReactionType,
+    reaction: &ReactionType,
     user: &AuthenticatedUser,
   ) -> Result<(), sqlx::Error> {
     sqlx::query!(
@@ -87,46 +125,134 @@ impl ReactionStore {
           returning 1
         )
         update reaction_summary
-        set like_count = like_count - (
-              case when $3 = 'like' then (select count(*) from del) else 0 end
-            ),
+        set count = count - (select count(*) from del)::int,
             updated_at = now()
-        where bid_id = $1
+        where bid_id = $1 and reaction = $3
       ",
       bid_id,
       user.id,
-      reaction as ReactionType,
+      reaction as &ReactionType,
     )
     .execute(&self.pool)
     .await?;
     Ok(())
   }
 
+  ///
+  /// Returns the current reaction of given user to given bid, if any.
+  ///
+  pub async fn reaction_of(
+    &self,
+    bid_id: &Uuid,
+    user: &AuthenticatedUser,
+  ) -> Result<Option<ReactionType>, sqlx::Error> {
+    sqlx::query_scalar!(
+      r#"
+        select reaction as "reaction: ReactionType"
+        from reactions
+        where bid_id = $1 and user_id = $2
+      "#,
+      bid_id,
+      user.id,
+    )
+    .fetch_optional(&self.pool)
+    .await
+  }
+
   pub async fn reactions_summary(
     &self,
     bid_id: &Uuid,
     user: Option<&AuthenticatedUser>,
   ) -> Result<ReactionSummary, sqlx::Error> {
-    let rec = sqlx::query!(
-      "
-        select
-          coalesce(rs.like_count, 0) as \"like_count!\",
-          r.reaction as \"viewer_reaction: ReactionType\"
-        from (select $1::uuid as bid_id) b
-        left join reaction_summary rs on rs.bid_id = b.bid_id
-        left join reactions r
-          on r.bid_id = b.bid_id
-          and r.user_id = $2
-      ",
+    let counts = sqlx::query!(
+      r#"
+        select reaction as "reaction: ReactionType", count
+        from reaction_summary
+        where bid_id = $1 and count > 0
+      "#,
       bid_id,
-      user.map(|u| u.id),
     )
-    .fetch_one(&self.pool)
-    .await?;
+    .fetch_all(&self.pool)
+    .await?
+    .into_iter()
+    .map(|rec| (rec.reaction, rec.count))
+    .collect();
+
+    let viewer_reaction = match user {
+      Some(user) => self.reaction_of(bid_id, user).await?,
+      None => None,
+    };
 
     Ok(ReactionSummary {
-      like_count: rec.like_count,
-      viewer_reaction: rec.viewer_reaction,
+      counts,
+      viewer_reaction,
     })
   }
+
+  pub async fn top_bids(
+    &self,
+    leaderboard: &Leaderboard,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
+    let (from, to) = leaderboard.region.unzip();
+
+    let rows = sqlx::query!(
+      r#"
+        select
+          bids.id, bids.bidder, bids.tx, bids.x, bids.y, bids.content, bids.amount,
+          bids.created_at, bids.published_at, bids.rejection,
+          profile.handle as "handle?",
+          (tile.occupant_bid is not null) as "live!",
+          sum(rs.count)::int as "reactions!"
+        from reaction_summary rs
+        join bids on bids.id = rs.bid_id
+        left join published_tiles tile on tile.occupant_bid = bids.id
+        left join profiles profile on profile.user_id = bids.bidder and profile.public
+        where bids.published_at is not null
+          and bids.rejection is null
+          and (not $1 or tile.occupant_bid is not null)
+          and ($2::varchar is null or rs.reaction = $2)
+          and ($3::int is null or bids.x between least($3, $5) and greatest($3, $5))
+          and ($4::int is null or bids.y between least($4, $6) and greatest($4, $6))
+        group by bids.id, tile.occupant_bid, profile.handle
+        having sum(rs.count) > 0
+        order by "reactions!" desc, bids.published_at desc
+        offset $7 limit $8
+      "#,
+      leaderboard.live_only,
+      leaderboard.reaction.as_ref().map(|r| r.0.clone()),
+      from.map(|c| c.x),
+      from.map(|c| c.y),
+      to.map(|c| c.x),
+      to.map(|c| c.y),
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| LeaderboardEntry {
+          bid: Bid {
+            id: row.id,
+            bidder: row.bidder,
+            tx: row.tx,
+            x: row.x,
+            y: row.y,
+            content: BidContent::from(row.content),
+            amount: row.amount,
+            created_at: row.created_at,
+            published_at: row.published_at,
+            rejection: row.rejection,
+          },
+          handle: row.handle,
+          reactions: row.reactions,
+          live: row.live,
+        })
+        .collect(),
+    )
+  }
 }
@@ -0,0 +1,97 @@
+use serde::Serialize;
+use sqlx::{postgres::Postgres, Pool};
+
+use super::super::book::{Bid, BidContent};
+
+///
+/// A live bid ranked by its recent reactions. `score` is the sum
+/// of weights of recent reactions, each reaction's weight halving
+/// every few hours. `handle` is the handle of the bidder, if they
+/// have a public profile.
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendingEntry {
+  pub bid: Bid,
+  pub handle: Option<String>,
+  pub score: f64,
+  pub reactions: i32,
+}
+
+///
+/// Ranks live bids by how fast they are gathering reactions. The ranking
+/// is expensive to compute, so it is kept in a materialized view, which
+/// is recomputed periodically by a scheduled job (see `Trending::refresh()`).
+///
+#[derive(Debug, Clone)]
+pub struct Trending {
+  pool: Pool<Postgres>,
+}
+
+impl Trending {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  ///
+  /// Recomputes the ranking. The view is refreshed concurrently, so
+  /// trending bids can still be read while it is being recomputed.
+  ///
+  pub async fn refresh(&self) -> Result<(), sqlx::Error> {
+    sqlx::query!("refresh materialized view concurrently trending_bids")
+      .execute(&self.pool)
+      .await?;
+
+    Ok(())
+  }
+
+  ///
+  /// Returns trending bids, i.e. live bids that gathered the most
+  /// reactions recently. Bids that stopped being live since the ranking
+  /// was computed are excluded.
+  ///
+  pub async fn top(&self, offset: u32, limit: u32) -> Result<Vec<TrendingEntry>, sqlx::Error> {
+    let rows = sqlx::query!(
+      r#"
+        select
+          bids.id, bids.bidder, bids.tx, bids.x, bids.y, bids.content, bids.amount,
+          bids.created_at, bids.published_at, bids.rejection,
+          profile.handle as "handle?",
+          trending.score as "score!",
+          trending.reactions as "reactions!"
+        from trending_bids trending
+        join bids on bids.id = trending.bid_id
+        join published_tiles tile on tile.occupant_bid = bids.id
+        left join profiles profile on profile.user_id = bids.bidder and profile.public
+        order by trending.score desc, bids.published_at desc
+        offset $1 limit $2
+      "#,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| TrendingEntry {
+          bid: Bid {
+            id: row.id,
+            bidder: row.bidder,
+            tx: row.tx,
+            x: row.x,
+            y: row.y,
+            content: BidContent::from(row.content),
+            amount: row.amount,
+            created_at: row.created_at,
+            published_at: row.published_at,
+            rejection: row.rejection,
+          },
+          handle: row.handle,
+          score: row.score,
+          reactions: row.reactions,
+        })
+        .collect(),
+    )
+  }
+}
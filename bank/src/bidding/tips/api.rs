@@ -0,0 +1,127 @@
+use axum::{
+  extract::{Extension, Json, Path},
+  http::HeaderMap,
+  response::IntoResponse,
+};
+use log::error;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::auth::{step_up::StepUps, ActiveUser};
+use crate::wallet::{error::WalletError, Account, Ledger};
+
+use super::super::book::{Bid, Book, Coords};
+use super::super::error::BiddingError;
+use super::storage::TipStore;
+
+async fn occupant(book: &Book, coords: &Coords) -> Result<Bid, BiddingError> {
+  match book.get_occupant_bid(coords).await {
+    Ok(Some(bid)) => Ok(bid),
+    Ok(None) => Err(BiddingError::NotFound),
+    Err(_) => Err(BiddingError::Unknown),
+  }
+}
+
+fn from_wallet_error(err: &WalletError) -> BiddingError {
+  match *err {
+    WalletError::InsufficientFunds => BiddingError::InsufficientFunds,
+    WalletError::ErroneousTransaction => BiddingError::IncorrectTransaction,
+    WalletError::LimitExceeded { resets_at } => BiddingError::LimitExceeded(resets_at),
+    _ => BiddingError::Unknown,
+  }
+}
+
+///
+/// Returns the tips received by the current occupant of given tile.
+///
+pub async fn tips(
+  Extension(book): Extension<Book>,
+  Extension(tips): Extension<TipStore>,
+  Path(coords): Path<Coords>,
+) -> Result<impl IntoResponse, BiddingError> {
+  let bid = occupant(&book, &coords).await?;
+
+  tips.summary(&bid.id).await.map(Json).map_err(|err| {
+    error!("Failed to sum up tips of {}: {err:?}", bid.id);
+    BiddingError::Unknown
+  })
+}
+
+#[derive(Deserialize)]
+pub struct TipRequest {
+  pub amount: u32,
+}
+
+///
+/// Tips the occupant of given tile, by offering given amount from
+/// the balance of the user to the bidder of the occupant bid. The
+/// occupant can then accept (or reject) the tip like any other offer. Tips taking the user's
+/// spending of the day above the step-up threshold need a step-up confirmation of
+/// `{ "action": "bids.tip", "x": ..., "y": ..., "amount": ... }`.
+///
+#[allow(clippy::too_many_arguments)]
+pub async fn tip(
+  Extension(book): Extension<Book>,
+  Extension(ledger): Extension<Ledger>,
+  Extension(tips): Extension<TipStore>,
+  Extension(step_ups): Extension<StepUps>,
+  Path(coords): Path<Coords>,
+  ActiveUser(user): ActiveUser,
+  headers: HeaderMap,
+  Json(req): Json<TipRequest>,
+) -> Result<impl IntoResponse, BiddingError> {
+  let bid = occupant(&book, &coords).await?;
+  if bid.bidder == user.id {
+    return Err(BiddingError::OwnTile);
+  }
+
+  let sender = Account::of_user(&user.id);
+
+  // the confirmation is only used up once the tip is known to go through.
+  if ledger
+    .check_offer_from_balance(&sender, req.amount, &user)
+    .await
+    .map_err(|err| from_wallet_error(&err))?
+  {
+    let operation = json!({
+      "action": "bids.tip",
+      "x": coords.x,
+      "y": coords.y,
+      "amount": req.amount,
+    });
+    match step_ups.verify(&headers, &user, &operation).await {
+      Ok(true) => {}
+      Ok(false) => return Err(BiddingError::StepUpRequired),
+      Err(err) => {
+        error!("Failed to verify step-up of {}: {err:?}", user.id);
+        return Err(BiddingError::Unknown);
+      }
+    }
+  }
+
+  let result = ledger
+    .offer_from_balance(
+      &sender,
+      &Account::of_user(&bid.bidder),
+      req.amount,
+      Some(format!("tip for {coords}, bid {}", bid.id)),
+      None,
+      &user,
+    )
+    .await
+    .map_err(|err| from_wallet_error(&err))?;
+
+  // the ledger and tips don't share a database transaction, so if the tip can't
+  // be linked to the bid, the offer is rescinded and the tip fails as a whole.
+  if let Some(tx) = &result.offer.id {
+    if let Err(err) = tips.record(tx, &bid.id, &user).await {
+      error!("Failed to record tip {tx} for {}: {err:?}", bid.id);
+      if let Err(err) = ledger.rescind_offer(&result.offer, &user).await {
+        error!("Failed to rescind unrecorded tip {tx}: {err:?}");
+      }
+      return Err(BiddingError::Unknown);
+    }
+  }
+
+  Ok(Json(result))
+}
@@ -0,0 +1,19 @@
+use axum::{
+  extract::Extension,
+  routing::{get, post},
+  Router,
+};
+use sqlx::{postgres::Postgres, Pool};
+use storage::TipStore;
+
+mod api;
+mod storage;
+
+pub fn router(db: &Pool<Postgres>) -> Router {
+  let tips = TipStore::new(db.clone());
+
+  Router::new()
+    .route("/", get(api::tips))
+    .route("/", post(api::tip))
+    .layer(Extension(tips))
+}
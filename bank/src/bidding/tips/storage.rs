@@ -0,0 +1,70 @@
+use serde::Serialize;
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+
+use crate::auth::AuthenticatedUser;
+
+///
+/// Tips received by a bid. Tips that were rejected by the
+/// occupant (or rescinded by the tipper) are not counted.
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct TipSummary {
+  pub total: i64,
+  pub count: i64,
+}
+
+#[derive(Clone)]
+pub struct TipStore {
+  pool: Pool<Postgres>,
+}
+
+impl TipStore {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  ///
+  /// Links given offer to the bid it tips.
+  ///
+  pub async fn record(
+    &self,
+    tx: &Uuid,
+    bid_id: &Uuid,
+    tipper: &AuthenticatedUser,
+  ) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+      "insert into bid_tips (tx, bid_id, tipper) values ($1, $2, $3)",
+      tx,
+      bid_id,
+      tipper.id,
+    )
+    .execute(&self.pool)
+    .await?;
+
+    Ok(())
+  }
+
+  ///
+  /// Sums up tips of given bid. Tip offers that are pending (not consumed yet)
+  /// or accepted (consumed into the state of their receiver) are counted, while
+  /// rejected, rescinded or expired ones (consumed back into the state of the
+  /// tipper) are not.
+  ///
+  pub async fn summary(&self, bid_id: &Uuid) -> Result<TipSummary, sqlx::Error> {
+    sqlx::query_as!(
+      TipSummary,
+      r#"
+        select
+          coalesce(sum(tx.consumed_value), 0)::bigint as "total!",
+          count(*) as "count!"
+        from bid_tips tip
+        join transactions tx on tx.id = tip.tx
+        left join transactions used on used.consumes = tx.id and used.is_state
+        where tip.bid_id = $1 and (not tx.consumed or used.receiver = tx.receiver)
+      "#,
+      bid_id,
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+}
@@ -0,0 +1,110 @@
+use log::error;
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+
+use super::book::{Bid, Book};
+use super::config::Config;
+use super::error::BiddingError;
+use super::publisher::Publisher;
+use crate::auth::AuthenticatedUser;
+use crate::wallet::Ledger;
+
+///
+/// Withdraws users from the grid, e.g. when they are suspended
+/// (see `auth::users::suspend()`), by unpublishing their live tiles
+/// and rejecting (and refunding) their pending bids.
+///
+#[derive(Clone)]
+pub struct Withdrawals {
+  book: Book,
+  ledger: Ledger,
+  publisher: Publisher,
+}
+
+impl Withdrawals {
+  pub fn new(config: Config, ledger: &Ledger, db: &Pool<Postgres>) -> Self {
+    Self {
+      book: Book::new(config, db.clone()),
+      ledger: ledger.clone(),
+      publisher: Publisher::from_env(),
+    }
+  }
+
+  ///
+  /// Unpublishes all live tiles of given user.
+  ///
+  /// ### Returns:
+  /// The bids that were unpublished.
+  ///
+  pub async fn unpublish_all(&self, user_id: &Uuid) -> Result<Vec<Bid>, BiddingError> {
+    let mut unpublished = vec![];
+    let live = self
+      .book
+      .all_live_bids(Some(*user_id), 0, u32::MAX)
+      .await
+      .map_err(|_| BiddingError::Unknown)?;
+
+    for mut bid in live {
+      self
+        .book
+        .unpublish(&mut bid)
+        .await
+        .map_err(|_| BiddingError::Unknown)?;
+      self
+        .publisher
+        .unpublish(&bid.coords())
+        .await
+        .map_err(|_| BiddingError::Unknown)?;
+      unpublished.push(bid);
+    }
+
+    Ok(unpublished)
+  }
+
+  ///
+  /// Rejects all pending bids of given user, refunding their offers.
+  /// Safe to retry if it fails midway.
+  ///
+  /// ### Returns:
+  /// The bids that were rejected.
+  ///
+  pub async fn refund_all(
+    &self,
+    user_id: &Uuid,
+    reason: &str,
+    admin: &AuthenticatedUser,
+  ) -> Result<Vec<Bid>, BiddingError> {
+    let mut refunded = vec![];
+    let pending = self.book.get_open_bids_of(user_id).await.map_err(|err| {
+      error!("Can't retrieve pending bids of {user_id}: {err:#}");
+      BiddingError::Unknown
+    })?;
+
+    // offers are refunded before their bids are rejected, so that if
+    // anything fails in between, retrying skips the refunded offer and
+    // only rejects the bid (rejected bids are never refunded otherwise).
+    for mut bid in pending {
+      let tx = self
+        .ledger
+        .get_transaction(&bid.tx)
+        .await
+        .map_err(|_| BiddingError::IncorrectTransaction)?;
+      if !tx.is_used() {
+        self
+          .ledger
+          .rescind_offer(&tx, admin)
+          .await
+          .map_err(|_| BiddingError::Unknown)?;
+      }
+
+      self
+        .book
+        .reject(&mut bid, admin, reason)
+        .await
+        .map_err(|_| BiddingError::Unknown)?;
+
+      refunded.push(bid);
+    }
+
+    Ok(refunded)
+  }
+}
@@ -0,0 +1,154 @@
+use axum::{
+  extract::{Extension, Json, Path, Query},
+  response::IntoResponse,
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use super::error::BookmarkError;
+use super::storage::BookmarkStore;
+use crate::auth::AuthenticatedUser;
+
+const MAX_LABEL_LENGTH: usize = 64;
+const MAX_IMPORT_SIZE: usize = 1024;
+
+fn validate_label(label: Option<&String>) -> Result<Option<&str>, BookmarkError> {
+  match label.map(|label| label.trim()) {
+    Some(label) if label.chars().count() > MAX_LABEL_LENGTH => Err(BookmarkError::InvalidLabel),
+    Some("") | None => Ok(None),
+    Some(label) => Ok(Some(label)),
+  }
+}
+
+#[derive(Deserialize)]
+pub struct BookmarksQuery {
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+///
+/// Returns bookmarks of the authenticated user, most recent first.
+///
+pub async fn bookmarks(
+  Extension(bookmarks): Extension<BookmarkStore>,
+  Query(BookmarksQuery { offset, limit }): Query<BookmarksQuery>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, BookmarkError> {
+  bookmarks
+    .bookmarks_of(&user, offset.unwrap_or(0), limit.unwrap_or(32))
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to fetch bookmarks of {}: {err:?}", user.email);
+      BookmarkError::Unknown
+    })
+}
+
+#[derive(Deserialize)]
+pub struct BookmarkRequest {
+  pub x: i32,
+  pub y: i32,
+  pub label: Option<String>,
+}
+
+///
+/// Bookmarks a tile for the authenticated user.
+///
+pub async fn bookmark(
+  Extension(bookmarks): Extension<BookmarkStore>,
+  user: AuthenticatedUser,
+  Json(req): Json<BookmarkRequest>,
+) -> Result<impl IntoResponse, BookmarkError> {
+  let label = validate_label(req.label.as_ref())?;
+
+  bookmarks
+    .add(&user, req.x, req.y, label)
+    .await
+    .map(Json)
+    .map_err(|err| match err {
+      sqlx::Error::Database(err) if err.is_unique_violation() => BookmarkError::AlreadyBookmarked,
+      err => {
+        error!("Failed to add bookmark for {}: {err:?}", user.email);
+        BookmarkError::Unknown
+      }
+    })
+}
+
+#[derive(Serialize)]
+pub struct ImportResult {
+  pub imported: u64,
+}
+
+///
+/// Bookmarks given tiles for the authenticated user in one go. This is meant for
+/// uploading bookmarks kept locally on a device (e.g. on first login), so tiles
+/// that are already bookmarked are skipped instead of failing the whole import.
+///
+pub async fn import(
+  Extension(bookmarks): Extension<BookmarkStore>,
+  user: AuthenticatedUser,
+  Json(req): Json<Vec<BookmarkRequest>>,
+) -> Result<impl IntoResponse, BookmarkError> {
+  if req.len() > MAX_IMPORT_SIZE {
+    return Err(BookmarkError::TooManyBookmarks);
+  }
+
+  let tiles = req
+    .iter()
+    .map(|tile| {
+      validate_label(tile.label.as_ref())
+        .map(|label| (tile.x, tile.y, label.map(ToString::to_string)))
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let imported = bookmarks.add_all(&user, &tiles).await.map_err(|err| {
+    error!("Failed to import bookmarks for {}: {err:?}", user.email);
+    BookmarkError::Unknown
+  })?;
+
+  Ok(Json(ImportResult { imported }))
+}
+
+#[derive(Deserialize)]
+pub struct RenameRequest {
+  pub label: Option<String>,
+}
+
+///
+/// Changes (or clears) the label of a bookmark of the authenticated user.
+///
+pub async fn rename(
+  Extension(bookmarks): Extension<BookmarkStore>,
+  Path(id): Path<Uuid>,
+  user: AuthenticatedUser,
+  Json(req): Json<RenameRequest>,
+) -> Result<impl IntoResponse, BookmarkError> {
+  let label = validate_label(req.label.as_ref())?;
+
+  bookmarks
+    .relabel(&user, &id, label)
+    .await
+    .map(Json)
+    .map_err(|err| match err {
+      sqlx::Error::RowNotFound => BookmarkError::NotFound,
+      _ => BookmarkError::Unknown,
+    })
+}
+
+///
+/// Removes a bookmark of the authenticated user.
+///
+pub async fn remove(
+  Extension(bookmarks): Extension<BookmarkStore>,
+  Path(id): Path<Uuid>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, BookmarkError> {
+  bookmarks
+    .remove(&user, &id)
+    .await
+    .map_err(|err| match err {
+      sqlx::Error::RowNotFound => BookmarkError::NotFound,
+      _ => BookmarkError::Unknown,
+    })
+}
@@ -0,0 +1,32 @@
+use axum::{
+  http::StatusCode,
+  response::{IntoResponse, Response},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum BookmarkError {
+  #[error("Unknown error")]
+  Unknown,
+  #[error("Bookmark not found")]
+  NotFound,
+  #[error("Already bookmarked")]
+  AlreadyBookmarked,
+  #[error("Invalid label")]
+  InvalidLabel,
+  #[error("Too many bookmarks")]
+  TooManyBookmarks,
+}
+
+impl IntoResponse for BookmarkError {
+  fn into_response(self) -> Response {
+    (match self {
+      BookmarkError::Unknown => (StatusCode::INTERNAL_SERVER_ERROR, "Unknown error"),
+      BookmarkError::NotFound => (StatusCode::NOT_FOUND, "Bookmark not found"),
+      BookmarkError::AlreadyBookmarked => (StatusCode::CONFLICT, "Already bookmarked"),
+      BookmarkError::InvalidLabel => (StatusCode::BAD_REQUEST, "Invalid label"),
+      BookmarkError::TooManyBookmarks => (StatusCode::BAD_REQUEST, "Too many bookmarks"),
+    })
+    .into_response()
+  }
+}
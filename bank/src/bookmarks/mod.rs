@@ -0,0 +1,35 @@
+use axum::{
+  extract::Extension,
+  routing::{delete, get, patch, post},
+  Router,
+};
+use sqlx::{postgres::Postgres, Pool};
+use tower_http::cors::{Any, CorsLayer};
+
+use storage::BookmarkStore;
+
+mod api;
+pub mod error;
+mod storage;
+
+///
+/// Builds a router for bookmarks of authenticated users,
+/// so that they are kept across devices.
+///
+pub fn router(db: &Pool<Postgres>) -> Router {
+  let cors = CorsLayer::new()
+    .allow_methods(Any)
+    .allow_headers(Any)
+    .allow_origin(Any);
+
+  let bookmarks = BookmarkStore::new(db.clone());
+
+  Router::new()
+    .route("/", get(api::bookmarks))
+    .route("/", post(api::bookmark))
+    .route("/import", post(api::import)) // --> bulk upload of local bookmarks
+    .route("/{id}", patch(api::rename))
+    .route("/{id}", delete(api::remove))
+    .layer(Extension(bookmarks))
+    .layer(cors)
+}
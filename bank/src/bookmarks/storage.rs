@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+
+use crate::auth::AuthenticatedUser;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+  pub id: Uuid,
+  pub x: i32,
+  pub y: i32,
+  pub label: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct BookmarkStore {
+  pool: Pool<Postgres>,
+}
+
+impl BookmarkStore {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  pub async fn bookmarks_of(
+    &self,
+    user: &AuthenticatedUser,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Bookmark>, sqlx::Error> {
+    sqlx::query_as!(
+      Bookmark,
+      "
+        select id, x, y, label, created_at, updated_at
+        from bookmarks
+        where user_id = $1
+        order by created_at desc
+        offset $2 limit $3
+      ",
+      user.id,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await
+  }
+
+  ///
+  /// Bookmarks given tile for given user. Fails with a unique
+  /// violation if the user has already bookmarked the tile.
+  ///
+  pub async fn add(
+    &self,
+    user: &AuthenticatedUser,
+    x: i32,
+    y: i32,
+    label: Option<&str>,
+  ) -> Result<Bookmark, sqlx::Error> {
+    sqlx::query_as!(
+      Bookmark,
+      "
+        insert into bookmarks (user_id, x, y, label)
+        values ($1, $2, $3, $4)
+        returning id, x, y, label, created_at, updated_at
+      ",
+      user.id,
+      x,
+      y,
+      label,
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+
+  ///
+  /// Bookmarks given tiles for given user, in one go. Tiles that
+  /// are already bookmarked are left untouched.
+  ///
+  /// ### Params:
+  /// - `user`: the user to bookmark tiles for
+  /// - `tiles`: the `(x, y, label)` of each tile
+  ///
+  /// ### Returns:
+  /// The number of newly added bookmarks.
+  ///
+  pub async fn add_all(
+    &self,
+    user: &AuthenticatedUser,
+    tiles: &[(i32, i32, Option<String>)],
+  ) -> Result<u64, sqlx::Error> {
+    let xs: Vec<i32> = tiles.iter().map(|(x, _, _)| *x).collect();
+    let ys: Vec<i32> = tiles.iter().map(|(_, y, _)| *y).collect();
+    let labels: Vec<Option<String>> = tiles.iter().map(|(_, _, label)| label.clone()).collect();
+
+    let res = sqlx::query!(
+      "
+        insert into bookmarks (user_id, x, y, label)
+        select $1, x, y, label
+        from unnest($2::int[], $3::int[], $4::varchar[]) as tile(x, y, label)
+        on conflict (user_id, x, y) do nothing
+      ",
+      user.id,
+      &xs,
+      &ys,
+      &labels as &[Option<String>],
+    )
+    .execute(&self.pool)
+    .await?;
+
+    Ok(res.rows_affected())
+  }
+
+  ///
+  /// Changes the label of given bookmark. Returns `RowNotFound`
+  /// if the bookmark doesn't exist or doesn't belong to given user.
+  ///
+  pub async fn relabel(
+    &self,
+    user: &AuthenticatedUser,
+    id: &Uuid,
+    label: Option<&str>,
+  ) -> Result<Bookmark, sqlx::Error> {
+    sqlx::query_as!(
+      Bookmark,
+      "
+        update bookmarks set label = $3, updated_at = now()
+        where id = $1 and user_id = $2
+        returning id, x, y, label, created_at, updated_at
+      ",
+      id,
+      user.id,
+      label,
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+
+  ///
+  /// Removes given bookmark. Returns `RowNotFound` if the bookmark
+  /// doesn't exist or doesn't belong to given user.
+  ///
+  pub async fn remove(&self, user: &AuthenticatedUser, id: &Uuid) -> Result<(), sqlx::Error> {
+    let res = sqlx::query!(
+      "delete from bookmarks where id = $1 and user_id = $2",
+      id,
+      user.id,
+    )
+    .execute(&self.pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+      Err(sqlx::Error::RowNotFound)
+    } else {
+      Ok(())
+    }
+  }
+}
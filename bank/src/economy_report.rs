@@ -0,0 +1,85 @@
+use chrono::{Duration, Utc};
+use log::{error, info};
+use serde::Serialize;
+use sqlx::{postgres::Postgres, Pool};
+use std::time::Instant;
+
+use super::config::Config;
+use super::wallet::economy::{self, EconomyTotals, SystemAccount};
+use super::wallet::Ledger;
+
+const PAGE_SIZE: u32 = 256;
+const SERIES_DAYS: i32 = 30;
+
+#[derive(Serialize)]
+struct EconomyReport {
+  current: EconomyTotals,
+  series: Vec<EconomyTotals>,
+  accounts: Vec<SystemAccount>,
+}
+
+///
+/// Prints a report of the economy as JSON to stdout: its current totals,
+/// daily totals of the last 30 days, and all system accounts with their holdings.
+///
+pub async fn economy_report(config: &Config, db: &Pool<Postgres>) {
+  info!("Generating economy report...");
+
+  let start = Instant::now();
+
+  let ledger = Ledger::new(config.wallet.clone(), db.clone());
+  let now = Utc::now();
+  let points = economy::series_points(
+    now - Duration::days(1) * SERIES_DAYS,
+    now,
+    Duration::days(1),
+    economy::MAX_SERIES_POINTS,
+  )
+  .unwrap_or_else(|| vec![now]);
+
+  let mut series = ledger.economy_at(&points).await.unwrap_or_else(|err| {
+    error!("Failed to fetch economy totals: {err:?}");
+    std::process::exit(2);
+  });
+  let current = series.last().cloned().unwrap_or_else(|| {
+    error!("Failed to fetch economy totals");
+    std::process::exit(2);
+  });
+  series.pop();
+
+  let mut accounts = vec![];
+  loop {
+    let page = ledger
+      .system_accounts(
+        None,
+        u32::try_from(accounts.len()).unwrap_or(u32::MAX),
+        PAGE_SIZE,
+      )
+      .await
+      .unwrap_or_else(|err| {
+        error!("Failed to fetch system accounts: {err:?}");
+        std::process::exit(2);
+      });
+    let done = page.len() < PAGE_SIZE as usize;
+    accounts.extend(page);
+
+    if done {
+      break;
+    }
+  }
+
+  info!(
+    "Supply: {}, system accounts: {}. ({:.2?})",
+    current.supply,
+    accounts.len(),
+    start.elapsed()
+  );
+
+  let report = EconomyReport {
+    current,
+    series,
+    accounts,
+  };
+
+  println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
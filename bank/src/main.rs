@@ -7,8 +7,16 @@ mod router;
 
 mod auth;
 mod bidding;
+mod bookmarks;
+mod economy_report;
 mod health;
+mod profiles;
+mod run_allowance;
 mod run_auctions;
+mod run_offer_expiry;
+mod run_simulation;
+mod run_trending;
+mod verify_ledger;
 mod wallet;
 
 #[tokio::main]
@@ -20,12 +28,28 @@ async fn main() {
     std::process::exit(1);
   });
 
-  let db = db::init().await;
-
   let mode = std::env::args().nth(1);
 
+  // simulations run on an in-memory ledger.
+  if mode == Some("simulate".to_string()) {
+    run_simulation::run_simulation(&conf).await;
+    return;
+  }
+
+  let db = db::init().await;
+
   if mode == Some("auctions".to_string()) {
     run_auctions::run_auctions(&conf, &db).await;
+  } else if mode == Some("allowance".to_string()) {
+    run_allowance::run_allowance(&conf, &db).await;
+  } else if mode == Some("expire-offers".to_string()) {
+    run_offer_expiry::run_offer_expiry(&conf, &db).await;
+  } else if mode == Some("trending".to_string()) {
+    run_trending::run_trending(&db).await;
+  } else if mode == Some("verify-ledger".to_string()) {
+    verify_ledger::verify_ledger(&conf, &db).await;
+  } else if mode == Some("economy".to_string()) {
+    economy_report::economy_report(&conf, &db).await;
   } else {
     router::start_server(&conf, &db).await;
   }
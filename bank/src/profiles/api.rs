@@ -0,0 +1,330 @@
+use axum::{
+  extract::{Extension, Json, Path, Query},
+  response::IntoResponse,
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use super::error::ProfileError;
+use super::follows::{Follower, Follows};
+use super::notifications::Notifications;
+use super::storage::{Profile, Profiles};
+use crate::auth::{ActiveUser, AuthenticatedUser};
+use crate::bidding::Bid;
+
+const MAX_DISPLAY_NAME_LENGTH: usize = 64;
+const MAX_BIO_LENGTH: usize = 280;
+const MAX_LINK_LENGTH: usize = 255;
+
+///
+/// Handles that would be shadowed by other routes.
+///
+const RESERVED_HANDLES: [&str; 3] = ["profile", "feed", "notifications"];
+
+fn validate_handle(handle: &str) -> Result<String, ProfileError> {
+  let handle = handle.trim().to_lowercase();
+
+  if !(3..=24).contains(&handle.len()) {
+    Err(ProfileError::InvalidProfile(
+      "handle must be 3 to 24 characters",
+    ))
+  } else if !handle
+    .chars()
+    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+  {
+    Err(ProfileError::InvalidProfile(
+      "handle can only contain letters, digits and underscores",
+    ))
+  } else if RESERVED_HANDLES.contains(&handle.as_str()) {
+    Err(ProfileError::HandleTaken)
+  } else {
+    Ok(handle)
+  }
+}
+
+///
+/// Checks whether given link is a valid web URL. Other schemes
+/// (e.g. `javascript:` or `data:`) are not allowed, as links are
+/// rendered on public profiles.
+///
+fn is_valid_link(link: &str) -> bool {
+  link.len() <= MAX_LINK_LENGTH
+    && url::Url::parse(link).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn optional(value: Option<&String>) -> Option<&str> {
+  value.map(|v| v.trim()).filter(|v| !v.is_empty())
+}
+
+///
+/// Returns the profile of the authenticated user,
+/// whether it is public or not.
+///
+pub async fn own_profile(
+  Extension(profiles): Extension<Profiles>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, ProfileError> {
+  match profiles.of(&user.id).await {
+    Ok(Some(profile)) => Ok(Json(profile)),
+    Ok(None) => Err(ProfileError::NotFound),
+    Err(err) => {
+      error!("Failed to fetch profile of {}: {err:?}", user.email);
+      Err(ProfileError::Unknown)
+    }
+  }
+}
+
+#[derive(Deserialize)]
+pub struct ProfileRequest {
+  pub handle: String,
+  pub display_name: String,
+  pub bio: Option<String>,
+  pub link: Option<String>,
+  #[serde(default)]
+  pub public: bool,
+}
+
+///
+/// Creates or updates the profile of the authenticated user.
+/// The profile is only visible to others if `public` is set.
+///
+pub async fn update_profile(
+  Extension(profiles): Extension<Profiles>,
+  ActiveUser(user): ActiveUser,
+  Json(req): Json<ProfileRequest>,
+) -> Result<impl IntoResponse, ProfileError> {
+  let handle = validate_handle(&req.handle)?;
+  let display_name = req.display_name.trim();
+  let bio = optional(req.bio.as_ref());
+  let link = optional(req.link.as_ref());
+
+  if display_name.is_empty() || display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+    return Err(ProfileError::InvalidProfile("invalid display name"));
+  }
+  if bio.is_some_and(|bio| bio.chars().count() > MAX_BIO_LENGTH) {
+    return Err(ProfileError::InvalidProfile("bio is too long"));
+  }
+  if link.is_some_and(|link| !is_valid_link(link)) {
+    return Err(ProfileError::InvalidProfile("invalid link"));
+  }
+
+  profiles
+    .save(&user.id, &handle, display_name, bio, link, req.public)
+    .await
+    .map(Json)
+    .map_err(|err| match err {
+      sqlx::Error::Database(err) if err.is_unique_violation() => ProfileError::HandleTaken,
+      err => {
+        error!("Failed to save profile of {}: {err:?}", user.email);
+        ProfileError::Unknown
+      }
+    })
+}
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct PublicProfile {
+  pub handle: String,
+  pub display_name: String,
+  pub bio: Option<String>,
+  pub link: Option<String>,
+  pub tiles: Vec<Bid>,
+}
+
+///
+/// Returns the public profile of the user with given handle,
+/// alongside the tiles they currently occupy.
+///
+pub async fn public_profile(
+  Extension(profiles): Extension<Profiles>,
+  Path(handle): Path<String>,
+  Query(PageQuery { offset, limit }): Query<PageQuery>,
+) -> Result<impl IntoResponse, ProfileError> {
+  let Profile {
+    user_id,
+    handle,
+    display_name,
+    bio,
+    link,
+    ..
+  } = match profiles.find_public(&handle).await {
+    Ok(Some(profile)) => profile,
+    Ok(None) => return Err(ProfileError::NotFound),
+    Err(_) => return Err(ProfileError::Unknown),
+  };
+
+  let tiles = profiles
+    .live_bids_of(&user_id, offset.unwrap_or(0), limit.unwrap_or(32))
+    .await
+    .map_err(|err| {
+      error!("Failed to fetch tiles of {handle}: {err:?}");
+      ProfileError::Unknown
+    })?;
+
+  Ok(Json(PublicProfile {
+    handle,
+    display_name,
+    bio,
+    link,
+    tiles,
+  }))
+}
+
+async fn find_public(profiles: &Profiles, handle: &str) -> Result<Profile, ProfileError> {
+  match profiles.find_public(handle).await {
+    Ok(Some(profile)) => Ok(profile),
+    Ok(None) => Err(ProfileError::NotFound),
+    Err(_) => Err(ProfileError::Unknown),
+  }
+}
+
+///
+/// Follows the creator with given handle. Only creators
+/// with public profiles can be followed.
+///
+pub async fn follow(
+  Extension(profiles): Extension<Profiles>,
+  Extension(follows): Extension<Follows>,
+  Path(handle): Path<String>,
+  ActiveUser(user): ActiveUser,
+) -> Result<impl IntoResponse, ProfileError> {
+  let followee = find_public(&profiles, &handle).await?;
+  if followee.user_id == user.id {
+    return Err(ProfileError::CannotFollowSelf);
+  }
+
+  follows
+    .follow(&user.id, &followee.user_id)
+    .await
+    .map_err(|err| {
+      error!("Failed to follow {handle}: {err:?}");
+      ProfileError::Unknown
+    })
+}
+
+///
+/// Unfollows the creator with given handle (or user id). Unlike following,
+/// the creator doesn't need a public profile, so creators who made their
+/// profile private (or removed it) can still be unfollowed.
+///
+pub async fn unfollow(
+  Extension(profiles): Extension<Profiles>,
+  Extension(follows): Extension<Follows>,
+  Path(handle): Path<String>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, ProfileError> {
+  let followee = match Uuid::parse_str(&handle) {
+    Ok(id) => id,
+    Err(_) => match profiles.find(&handle).await {
+      Ok(Some(profile)) => profile.user_id,
+      Ok(None) => return Err(ProfileError::NotFound),
+      Err(_) => return Err(ProfileError::Unknown),
+    },
+  };
+
+  follows
+    .unfollow(&user.id, &followee)
+    .await
+    .map_err(|err| match err {
+      sqlx::Error::RowNotFound => ProfileError::NotFound,
+      _ => ProfileError::Unknown,
+    })
+}
+
+#[derive(Serialize)]
+pub struct FollowersResponse {
+  pub count: i64,
+  pub followers: Vec<Follower>,
+}
+
+///
+/// Returns followers of the creator with given handle. All followers
+/// are counted, but only those with public profiles are listed.
+///
+pub async fn followers(
+  Extension(profiles): Extension<Profiles>,
+  Extension(follows): Extension<Follows>,
+  Path(handle): Path<String>,
+  Query(PageQuery { offset, limit }): Query<PageQuery>,
+) -> Result<impl IntoResponse, ProfileError> {
+  let followee = find_public(&profiles, &handle).await?;
+
+  let count = follows
+    .count_followers(&followee.user_id)
+    .await
+    .map_err(|_| ProfileError::Unknown)?;
+  let followers = follows
+    .public_followers_of(&followee.user_id, offset.unwrap_or(0), limit.unwrap_or(32))
+    .await
+    .map_err(|_| ProfileError::Unknown)?;
+
+  Ok(Json(FollowersResponse { count, followers }))
+}
+
+///
+/// Returns bids recently published by creators the
+/// authenticated user follows, most recent first.
+///
+pub async fn feed(
+  Extension(follows): Extension<Follows>,
+  Query(PageQuery { offset, limit }): Query<PageQuery>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, ProfileError> {
+  follows
+    .feed_of(&user.id, offset.unwrap_or(0), limit.unwrap_or(32))
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to fetch feed of {}: {err:?}", user.email);
+      ProfileError::Unknown
+    })
+}
+
+#[derive(Deserialize)]
+pub struct NotificationsQuery {
+  #[serde(default)]
+  pub unread: bool,
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+///
+/// Returns notifications of the authenticated user, most recent first.
+///
+pub async fn notifications(
+  Extension(notifications): Extension<Notifications>,
+  Query(NotificationsQuery {
+    unread,
+    offset,
+    limit,
+  }): Query<NotificationsQuery>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, ProfileError> {
+  notifications
+    .of(&user.id, unread, offset.unwrap_or(0), limit.unwrap_or(32))
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to fetch notifications of {}: {err:?}", user.email);
+      ProfileError::Unknown
+    })
+}
+
+///
+/// Marks all notifications of the authenticated user as read.
+///
+pub async fn read_notifications(
+  Extension(notifications): Extension<Notifications>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, ProfileError> {
+  notifications
+    .mark_read(&user.id)
+    .await
+    .map_err(|_| ProfileError::Unknown)
+}
@@ -0,0 +1,38 @@
+use axum::{
+  http::StatusCode,
+  response::{IntoResponse, Response},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ProfileError {
+  #[error("Unknown error")]
+  Unknown,
+  #[error("Profile not found")]
+  NotFound,
+  #[error("Invalid profile: {0}")]
+  InvalidProfile(&'static str),
+  #[error("Handle already taken")]
+  HandleTaken,
+  #[error("Cannot follow self")]
+  CannotFollowSelf,
+}
+
+impl IntoResponse for ProfileError {
+  fn into_response(self) -> Response {
+    (match self {
+      ProfileError::Unknown => (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Unknown error".to_string(),
+      ),
+      ProfileError::NotFound => (StatusCode::NOT_FOUND, "Profile not found".to_string()),
+      ProfileError::InvalidProfile(reason) => (
+        StatusCode::BAD_REQUEST,
+        format!("Invalid profile: {reason}"),
+      ),
+      ProfileError::HandleTaken => (StatusCode::CONFLICT, "Handle already taken".to_string()),
+      ProfileError::CannotFollowSelf => (StatusCode::BAD_REQUEST, "Cannot follow self".to_string()),
+    })
+    .into_response()
+  }
+}
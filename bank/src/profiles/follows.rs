@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+
+use crate::bidding::Bid;
+
+///
+/// A follower of some user. Only followers with public
+/// profiles are listed, the rest are only counted.
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct Follower {
+  pub handle: String,
+  pub display_name: String,
+  pub followed_at: DateTime<Utc>,
+}
+
+///
+/// Keeps track of which users follow which creators. Followers
+/// are notified when creators they follow publish a bid
+/// (see the `follows` migration).
+///
+#[derive(Debug, Clone)]
+pub struct Follows {
+  pool: Pool<Postgres>,
+}
+
+impl Follows {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  pub async fn follow(&self, user_id: &Uuid, creator: &Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+      "
+        insert into follows (follower, followee) values ($1, $2)
+        on conflict do nothing
+      ",
+      user_id,
+      creator,
+    )
+    .execute(&self.pool)
+    .await?;
+
+    Ok(())
+  }
+
+  ///
+  /// Unfollows given user. Returns `RowNotFound` if
+  /// the user wasn't following them.
+  ///
+  pub async fn unfollow(&self, user_id: &Uuid, creator: &Uuid) -> Result<(), sqlx::Error> {
+    let res = sqlx::query!(
+      "delete from follows where follower = $1 and followee = $2",
+      user_id,
+      creator,
+    )
+    .execute(&self.pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+      Err(sqlx::Error::RowNotFound)
+    } else {
+      Ok(())
+    }
+  }
+
+  pub async fn count_followers(&self, creator: &Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+      r#"select count(*) as "count!" from follows where followee = $1"#,
+      creator,
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+
+  ///
+  /// Returns followers of given user that have public profiles,
+  /// most recent followers first.
+  ///
+  pub async fn public_followers_of(
+    &self,
+    creator: &Uuid,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Follower>, sqlx::Error> {
+    sqlx::query_as!(
+      Follower,
+      "
+        select profiles.handle, profiles.display_name, follows.created_at as followed_at
+        from follows
+        join profiles on profiles.user_id = follows.follower and profiles.public
+        where follows.followee = $1
+        order by follows.created_at desc
+        offset $2 limit $3
+      ",
+      creator,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await
+  }
+
+  ///
+  /// Returns bids of creators followed by given user, most recently
+  /// published first. Rejected bids are excluded.
+  ///
+  pub async fn feed_of(
+    &self,
+    user_id: &Uuid,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Bid>, sqlx::Error> {
+    sqlx::query_as!(
+      Bid,
+      "
+        select bids.* from follows
+        join profiles on profiles.user_id = follows.followee and profiles.public
+        join bids on bids.bidder = follows.followee
+        where follows.follower = $1
+          and bids.published_at is not null
+          and bids.rejection is null
+        order by bids.published_at desc
+        offset $2 limit $3
+      ",
+      user_id,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await
+  }
+}
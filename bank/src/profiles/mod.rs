@@ -0,0 +1,51 @@
+use axum::{
+  extract::Extension,
+  routing::{delete, get, post, put},
+  Router,
+};
+use sqlx::{postgres::Postgres, Pool};
+use tower_http::cors::{Any, CorsLayer};
+
+use follows::Follows;
+use notifications::Notifications;
+
+mod api;
+pub mod error;
+mod follows;
+mod notifications;
+mod storage;
+
+pub use storage::Profiles;
+
+///
+/// Builds a router for user profiles, including:
+/// - managing own profile
+/// - viewing public profiles (and tiles) of other users
+/// - following creators, and a feed of what they publish
+/// - notifications of the user
+///
+/// Requires `Profiles` to be provided as an extension.
+///
+pub fn router(db: &Pool<Postgres>) -> Router {
+  let cors = CorsLayer::new()
+    .allow_methods(Any)
+    .allow_headers(Any)
+    .allow_origin(Any);
+
+  let follows = Follows::new(db.clone());
+  let notifications = Notifications::new(db.clone());
+
+  Router::new()
+    .route("/profile", get(api::own_profile))
+    .route("/profile", put(api::update_profile))
+    .route("/feed", get(api::feed)) // --> recently published bids of followed creators
+    .route("/notifications", get(api::notifications))
+    .route("/notifications/read", post(api::read_notifications))
+    .route("/{handle}", get(api::public_profile))
+    .route("/{handle}/follow", post(api::follow))
+    .route("/{handle}/follow", delete(api::unfollow))
+    .route("/{handle}/followers", get(api::followers))
+    .layer(Extension(follows))
+    .layer(Extension(notifications))
+    .layer(cors)
+}
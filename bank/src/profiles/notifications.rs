@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+
+///
+/// An event addressed to a specific user, e.g. a creator they
+/// follow publishing a new tile (`followee.published`).
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+  pub id: Uuid,
+  pub kind: String,
+  pub payload: Value,
+  pub created_at: DateTime<Utc>,
+  pub read_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notifications {
+  pool: Pool<Postgres>,
+}
+
+impl Notifications {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  pub async fn of(
+    &self,
+    user_id: &Uuid,
+    unread_only: bool,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Notification>, sqlx::Error> {
+    sqlx::query_as!(
+      Notification,
+      "
+        select id, kind, payload, created_at, read_at
+        from notifications
+        where user_id = $1 and (not $2 or read_at is null)
+        order by created_at desc
+        offset $3 limit $4
+      ",
+      user_id,
+      unread_only,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await
+  }
+
+  ///
+  /// Marks all notifications of given user as read.
+  ///
+  pub async fn mark_read(&self, user_id: &Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+      "update notifications set read_at = now() where user_id = $1 and read_at is null",
+      user_id,
+    )
+    .execute(&self.pool)
+    .await?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
+
+use crate::bidding::Bid;
+
+///
+/// The profile of a user. Profiles are opt-in, and are
+/// only shown to others when marked `public`.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+  pub user_id: Uuid,
+  pub handle: String,
+  pub display_name: String,
+  pub bio: Option<String>,
+  pub link: Option<String>,
+  pub public: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+///
+/// Keeps track of user profiles, and resolves which users
+/// can be publicly identified by their handles.
+///
+#[derive(Debug, Clone)]
+pub struct Profiles {
+  pool: Pool<Postgres>,
+}
+
+impl Profiles {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+
+  pub async fn of(&self, user_id: &Uuid) -> Result<Option<Profile>, sqlx::Error> {
+    sqlx::query_as!(
+      Profile,
+      "select * from profiles where user_id = $1",
+      user_id
+    )
+    .fetch_optional(&self.pool)
+    .await
+  }
+
+  ///
+  /// Finds the profile with given handle, whether it is public or not.
+  ///
+  pub async fn find(&self, handle: &str) -> Result<Option<Profile>, sqlx::Error> {
+    sqlx::query_as!(
+      Profile,
+      "select * from profiles where handle = lower($1)",
+      handle
+    )
+    .fetch_optional(&self.pool)
+    .await
+  }
+
+  pub async fn find_public(&self, handle: &str) -> Result<Option<Profile>, sqlx::Error> {
+    sqlx::query_as!(
+      Profile,
+      "select * from profiles where handle = lower($1) and public",
+      handle
+    )
+    .fetch_optional(&self.pool)
+    .await
+  }
+
+  ///
+  /// Returns the handle of given user, if they have a public profile.
+  ///
+  pub async fn public_handle_of(&self, user_id: &Uuid) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+      "select handle from profiles where user_id = $1 and public",
+      user_id
+    )
+    .fetch_optional(&self.pool)
+    .await
+  }
+
+  ///
+  /// Creates or updates the profile of given user. Fails with a
+  /// unique violation if the handle is taken by another user.
+  ///
+  pub async fn save(
+    &self,
+    user_id: &Uuid,
+    handle: &str,
+    display_name: &str,
+    bio: Option<&str>,
+    link: Option<&str>,
+    public: bool,
+  ) -> Result<Profile, sqlx::Error> {
+    sqlx::query_as!(
+      Profile,
+      "
+        insert into profiles (user_id, handle, display_name, bio, link, public)
+        values ($1, $2, $3, $4, $5, $6)
+        on conflict (user_id) do update set
+          handle = excluded.handle,
+          display_name = excluded.display_name,
+          bio = excluded.bio,
+          link = excluded.link,
+          public = excluded.public,
+          updated_at = now()
+        returning *
+      ",
+      user_id,
+      handle,
+      display_name,
+      bio,
+      link,
+      public,
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+
+  ///
+  /// Returns bids of given user that currently occupy their tiles,
+  /// most recently published first.
+  ///
+  pub async fn live_bids_of(
+    &self,
+    user_id: &Uuid,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Bid>, sqlx::Error> {
+    sqlx::query_as!(
+      Bid,
+      "
+        select bids.* from published_tiles
+        join bids on published_tiles.occupant_bid = bids.id
+        where bids.bidder = $1
+        order by bids.published_at desc
+        offset $2 limit $3
+      ",
+      user_id,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.pool)
+    .await
+  }
+}
@@ -1,18 +1,27 @@
+use std::net::SocketAddr;
+
 use axum::{extract::Extension, Router};
 use log::info;
 use sqlx::{postgres::Postgres, Pool};
 
 use super::auth;
 use super::bidding;
+use super::bookmarks;
 use super::config::Config;
 use super::health;
+use super::profiles;
 use super::wallet;
 
 pub async fn start_server(config: &Config, db: &Pool<Postgres>) {
   info!("Starting server");
 
   let admin = auth::admin::AdminConfig::init();
+  let audit = auth::audit::AuditLog::new(db.clone());
+  let suspensions = auth::suspension::Suspensions::new(db.clone());
+  let step_ups = auth::step_up::StepUps::new(db.clone());
+  let profiles = profiles::Profiles::new(db.clone());
   let ledger = wallet::Ledger::new(config.wallet.clone(), db.clone());
+  let withdrawals = bidding::Withdrawals::new(config.bidding.clone(), &ledger, db);
 
   let app = Router::new()
     .nest("/auth", auth::router(db))
@@ -21,8 +30,16 @@ pub async fn start_server(config: &Config, db: &Pool<Postgres>) {
       "/bids",
       bidding::router(config.bidding.clone(), &ledger, db),
     )
+    .nest("/bookmarks", bookmarks::router(db))
+    .nest("/users", profiles::router(db))
+    .nest("/admin", auth::audit::router())
     .nest("/health", health::router())
-    .layer(Extension(admin));
+    .layer(Extension(admin))
+    .layer(Extension(audit))
+    .layer(Extension(suspensions))
+    .layer(Extension(step_ups))
+    .layer(Extension(profiles))
+    .layer(Extension(withdrawals));
 
   let host = std::env::var("HOST").unwrap_or("127.0.0.1".to_string());
   let port = std::env::var("PORT")
@@ -35,5 +52,10 @@ pub async fn start_server(config: &Config, db: &Pool<Postgres>) {
   info!("serving on {addr}");
 
   let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-  axum::serve(listener, app).await.unwrap();
+  axum::serve(
+    listener,
+    app.into_make_service_with_connect_info::<SocketAddr>(),
+  )
+  .await
+  .unwrap();
 }
@@ -0,0 +1,48 @@
+use chrono::Utc;
+use log::{error, info};
+use sqlx::{postgres::Postgres, Pool};
+use std::time::Instant;
+
+use super::config::Config;
+use super::wallet::Ledger;
+
+pub async fn run_allowance(config: &Config, db: &Pool<Postgres>) {
+  let allowance = &config.wallet.allowance;
+  if allowance.amount == 0 {
+    info!("Allowance is disabled, skipping.");
+    return;
+  }
+
+  info!("Paying allowances...");
+
+  let start = Instant::now();
+
+  let ledger = Ledger::new(config.wallet.clone(), db.clone());
+  let period = allowance.period_of(Utc::now());
+  let recipients = match ledger.allowance_recipients(allowance, period).await {
+    Ok(recipients) => recipients,
+    Err(err) => {
+      error!("❌ Failed fetching allowance recipients: {err:?}");
+      std::process::exit(1);
+    }
+  };
+
+  let mut paid = 0;
+  let mut failed = 0;
+
+  for recipient in &recipients {
+    match ledger.pay_allowance(recipient, period).await {
+      Ok(Some(_)) => paid += 1,
+      Ok(None) => {}
+      Err(err) => {
+        error!("❌ Failed paying allowance of {}: {err:?}", recipient.email);
+        failed += 1;
+      }
+    }
+  }
+
+  info!(
+    "Paid allowance of {period} to {paid} users, {failed} failed. ({:.2?})",
+    start.elapsed()
+  );
+}
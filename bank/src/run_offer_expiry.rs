@@ -0,0 +1,53 @@
+use log::{error, info};
+use sqlx::{postgres::Postgres, Pool};
+use std::time::Instant;
+
+use super::config::Config;
+use super::wallet::{error::WalletError, Ledger};
+
+///
+/// How many expired offers to fetch at once.
+///
+const BATCH_SIZE: u32 = 256;
+
+pub async fn run_offer_expiry(config: &Config, db: &Pool<Postgres>) {
+  info!("Expiring offers...");
+
+  let start = Instant::now();
+
+  let ledger = Ledger::new(config.wallet.clone(), db.clone());
+  let mut expired = 0;
+  let mut failed = 0;
+
+  loop {
+    let offers = ledger.find_expired_offers(BATCH_SIZE).await.unwrap();
+    let batch = offers.len();
+    let mut progressed = false;
+
+    for offer in offers {
+      match ledger.expire_offer(&offer).await {
+        Ok(_) => {
+          expired += 1;
+          progressed = true;
+        }
+        // accepted or rejected meanwhile, no longer open.
+        Err(WalletError::AlreadyUsedTransaction) => progressed = true,
+        Err(err) => {
+          error!("❌ Failed expiring offer {:?}: {err:?}", offer.id);
+          failed += 1;
+        }
+      }
+    }
+
+    // failing offers would be fetched again, so stop
+    // once a batch doesn't get any offers out of the way.
+    if batch < BATCH_SIZE as usize || !progressed {
+      break;
+    }
+  }
+
+  info!(
+    "Expired {expired} offers, {failed} failed. ({:.2?})",
+    start.elapsed()
+  );
+}
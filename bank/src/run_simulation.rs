@@ -0,0 +1,210 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+
+use log::{error, info};
+use rand::{
+  rngs::StdRng,
+  seq::{IndexedRandom, IteratorRandom},
+  Rng, SeedableRng,
+};
+use serde::Serialize;
+use sqlx::types::Uuid;
+
+use super::auth::{user::VerificationStatus, AuthenticatedUser};
+use super::config::Config;
+use super::wallet::store::MemoryStore;
+use super::wallet::{error::WalletError, Account, Ledger, Transaction};
+
+const USERS: usize = 32;
+const DEFAULT_STEPS: u32 = 10_000;
+
+#[derive(Serialize)]
+struct SimulationReport {
+  seed: u64,
+  steps: u32,
+  operations: BTreeMap<&'static str, u32>,
+  failures: BTreeMap<String, u32>,
+  transactions: usize,
+  issued: i64,
+  supply: i64,
+  conserved: bool,
+  single_live_states: bool,
+}
+
+fn simulated_user(i: usize) -> AuthenticatedUser {
+  AuthenticatedUser {
+    id: Uuid::new_v4(),
+    email: format!("user-{i}@simulation"),
+    first_name: format!("User {i}"),
+    last_name: String::new(),
+    verification: VerificationStatus::default(),
+    token: String::new(),
+  }
+}
+
+///
+/// Runs a random operation on behalf of a random user, returning the
+/// name of the operation and its result, or `None` if nothing was done.
+///
+async fn step(
+  ledger: &Ledger<MemoryStore>,
+  users: &[AuthenticatedUser],
+  rng: &mut StdRng,
+) -> Option<(&'static str, Result<(), WalletError>)> {
+  let user = users.choose(rng).unwrap();
+  let account = Account::of_user(&user.id);
+
+  Some(match rng.random_range(0..10) {
+    0..4 => {
+      // offering to oneself isn't a valid operation.
+      let other = users.iter().filter(|u| u.id != user.id).choose(rng)?;
+      let amount = rng.random_range(1..=8);
+      (
+        "offer",
+        ledger
+          .offer_from_balance(
+            &account,
+            &Account::of_user(&other.id),
+            amount,
+            None,
+            None,
+            user,
+          )
+          .await
+          .map(|_| ()),
+      )
+    }
+    4 => (
+      "inject",
+      ledger
+        .inject(&account, rng.random_range(1..=8), None, user)
+        .await
+        .map(|_| ()),
+    ),
+    _ => {
+      let offers = ledger
+        .find_open_offers(&account, 0, 32)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(Transaction::is_offer)
+        .collect::<Vec<_>>();
+      let offer = offers.choose(rng)?;
+
+      match rng.random_range(0..4) {
+        0 => ("accept", ledger.accept_offer(offer, user).await.map(|_| ())),
+        1 => (
+          "reject",
+          ledger.reject_offer(offer, None, user).await.map(|_| ()),
+        ),
+        2 => {
+          let amount = rng.random_range(0..=offer.total());
+          (
+            "partially_accept",
+            ledger
+              .partially_accept_offer(offer, amount, None, user)
+              .await
+              .map(|_| ()),
+          )
+        }
+        _ => {
+          let sender = users
+            .iter()
+            .find(|u| offer.sender == Some(u.id))
+            .unwrap_or(user);
+          (
+            "rescind",
+            ledger.rescind_offer(offer, sender).await.map(|_| ()),
+          )
+        }
+      }
+    }
+  })
+}
+
+///
+/// Runs random OCSL operations (offers, accepts, rejects, etc.) between simulated users on
+/// an in-memory ledger, without touching the database. Afterwards checks that no coins were
+/// created or destroyed outside of issuance, and that each account has a single live state,
+/// printing the report as JSON to stdout. Exits with a non-zero code if any check fails.
+///
+/// Usage: `simulate [steps] [seed]`, runs 10k steps with a random seed by default.
+///
+pub async fn run_simulation(config: &Config) {
+  let steps = std::env::args()
+    .nth(2)
+    .and_then(|arg| arg.parse().ok())
+    .unwrap_or(DEFAULT_STEPS);
+  let seed = std::env::args()
+    .nth(3)
+    .and_then(|arg| arg.parse().ok())
+    .unwrap_or_else(rand::random);
+
+  info!("Simulating {steps} steps (seed {seed})...");
+
+  let start = Instant::now();
+
+  let store = MemoryStore::default();
+  let ledger = Ledger::with_backend(config.wallet.clone(), store.clone());
+  let users = (0..USERS).map(simulated_user).collect::<Vec<_>>();
+  let mut rng = StdRng::seed_from_u64(seed);
+  let mut operations = BTreeMap::new();
+  let mut failures = BTreeMap::new();
+
+  for _ in 0..steps {
+    let Some((operation, result)) = step(&ledger, &users, &mut rng).await else {
+      continue;
+    };
+
+    *operations.entry(operation).or_insert(0) += 1;
+    if let Err(err) = result {
+      *failures.entry(format!("{operation}: {err}")).or_insert(0) += 1;
+    }
+  }
+
+  let transactions = store.transactions();
+  let live = transactions
+    .iter()
+    .filter(|tx| !tx.is_used())
+    .collect::<Vec<_>>();
+
+  // coins are only issued by states without a sender,
+  // i.e. initial balances and minting.
+  let issued = transactions
+    .iter()
+    .filter(|tx| tx.is_state && tx.consumes.is_none())
+    .map(|tx| i64::from(tx.consumed_value))
+    .sum::<i64>();
+  let supply = live.iter().map(|tx| i64::from(tx.total())).sum::<i64>();
+
+  let mut states = HashSet::new();
+  let single_live_states = live
+    .iter()
+    .filter(|tx| tx.is_state)
+    .all(|tx| states.insert(tx.receiver_account()));
+
+  let report = SimulationReport {
+    seed,
+    steps,
+    operations,
+    failures,
+    transactions: transactions.len(),
+    issued,
+    supply,
+    conserved: issued == supply,
+    single_live_states,
+  };
+
+  info!(
+    "Simulated {steps} steps, stored {} transactions. ({:.2?})",
+    report.transactions,
+    start.elapsed()
+  );
+
+  println!("{}", serde_json::to_string_pretty(&report).unwrap());
+
+  if !report.conserved || !report.single_live_states {
+    error!("Simulation broke ledger invariants (seed {seed})");
+    std::process::exit(1);
+  }
+}
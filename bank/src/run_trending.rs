@@ -0,0 +1,18 @@
+use log::{error, info};
+use sqlx::{postgres::Postgres, Pool};
+use std::time::Instant;
+
+use super::bidding::Trending;
+
+pub async fn run_trending(db: &Pool<Postgres>) {
+  info!("Refreshing trending bids...");
+
+  let start = Instant::now();
+
+  if let Err(err) = Trending::new(db.clone()).refresh().await {
+    error!("❌ Failed refreshing trending bids: {err:?}");
+    std::process::exit(1);
+  }
+
+  info!("Refreshed trending bids. ({:.2?})", start.elapsed());
+}
@@ -0,0 +1,36 @@
+use log::{error, info};
+use sqlx::{postgres::Postgres, Pool};
+use std::time::Instant;
+
+use super::config::Config;
+use super::wallet::Ledger;
+
+///
+/// Verifies the integrity of the ledger, printing the report as JSON
+/// to stdout. Exits with a non-zero code if any violations are found
+/// (or if the verification fails), so it can be run on a schedule.
+///
+pub async fn verify_ledger(config: &Config, db: &Pool<Postgres>) {
+  info!("Verifying ledger...");
+
+  let start = Instant::now();
+
+  let ledger = Ledger::new(config.wallet.clone(), db.clone());
+  let report = ledger.verify().await.unwrap_or_else(|err| {
+    error!("Failed to verify ledger: {err:?}");
+    std::process::exit(2);
+  });
+
+  info!(
+    "Checked {} transactions, found {} violations. ({:.2?})",
+    report.checked,
+    report.violations.len(),
+    start.elapsed()
+  );
+
+  println!("{}", serde_json::to_string_pretty(&report).unwrap());
+
+  if !report.ok {
+    std::process::exit(1);
+  }
+}
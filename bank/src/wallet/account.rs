@@ -3,7 +3,13 @@ use std::fmt::Display;
 use serde::{Deserialize, Serialize};
 use sqlx::types::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+///
+/// The system account new coins are minted into,
+/// before being offered to other accounts.
+///
+pub const MINT: &str = "mint";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 #[serde(tag = "type", content = "id")]
 pub enum Account {
   User(Uuid),
@@ -20,6 +26,10 @@ impl Account {
     Account::System(sys.to_string())
   }
 
+  pub fn mint() -> Self {
+    Account::System(MINT.to_string())
+  }
+
   pub fn from_tuple(user_id: Option<&Uuid>, sys: Option<&String>) -> Self {
     match (user_id, sys) {
       (Some(id), _) => Account::User(*id),
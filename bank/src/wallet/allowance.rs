@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::error;
+use serde::Deserialize;
+use sqlx::types::Uuid;
+
+use super::account::Account;
+use super::error::WalletError;
+use super::ledger::Ledger;
+use super::transaction::Transaction;
+
+///
+/// Configuration for the periodic allowance, paid to users who were active
+/// within the last period (placed a bid, used their wallet, reacted or commented).
+/// The allowance only tops up users' holdings (their balance plus open offers to them)
+/// up to the cap. Setting the amount to zero disables the allowance.
+///
+/// ### Example (TOML):
+/// ```toml
+/// amount = 8
+/// period = "7days"
+/// cap = 64
+/// ```
+///
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Allowance {
+  pub amount: u32,
+  #[serde(with = "humantime_serde")]
+  pub period: Duration,
+  pub cap: u32,
+}
+
+impl Default for Allowance {
+  fn default() -> Self {
+    Self {
+      amount: 0,
+      period: Duration::from_hours(7 * 24),
+      cap: 100,
+    }
+  }
+}
+
+impl Allowance {
+  ///
+  /// Returns the start of the period given time falls into.
+  /// Periods are aligned to the unix epoch.
+  ///
+  pub fn period_of(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+    let length = i64::try_from(self.period.as_secs())
+      .unwrap_or(i64::MAX)
+      .max(1);
+    let timestamp = time.timestamp();
+
+    DateTime::from_timestamp(timestamp - timestamp.rem_euclid(length), 0).unwrap_or(time)
+  }
+}
+
+///
+/// A user eligible for the allowance of some period,
+/// alongside the amount they are to be paid.
+///
+#[derive(Debug)]
+pub struct AllowanceRecipient {
+  pub id: Uuid,
+  pub email: String,
+  pub amount: i32,
+}
+
+impl Ledger {
+  ///
+  /// Returns users who should be paid the allowance of the period starting at given
+  /// time: users who weren't suspended, were active within the last period,
+  /// have holdings below the cap, and haven't been paid for this period yet. Users
+  /// are active if they placed bids, made offers (including rejections), reacted or
+  /// commented themselves, while transactions updating their state, or issued by
+  /// others (e.g. expiring offers to them), don't count.
+  ///
+  pub async fn allowance_recipients(
+    &self,
+    allowance: &Allowance,
+    period_start: DateTime<Utc>,
+  ) -> Result<Vec<AllowanceRecipient>, sqlx::Error> {
+    // periods too long to subtract from now cover all activity anyway.
+    let active_since = chrono::Duration::from_std(allowance.period)
+      .ok()
+      .and_then(|period| Utc::now().checked_sub_signed(period))
+      .unwrap_or(DateTime::UNIX_EPOCH);
+
+    sqlx::query_as!(
+      AllowanceRecipient,
+      r#"
+        with active as (
+          select bidder as user_id from bids where created_at >= $1
+          union
+          select sender from transactions
+          where sender = issued_by and is_state is false and created_at >= $1
+          union
+          select user_id from reactions where updated_at >= $1
+          union
+          select author from comments where created_at >= $1
+        ),
+        holdings as (
+          select receiver as user_id, sum(consumed_value + merged_value) as total
+          from transactions
+          where receiver is not null and consumed is false and merged is false
+          group by receiver
+        )
+        select
+          users.id, users.email,
+          least($3::bigint, $4::bigint - coalesce(holdings.total, 0))::integer as "amount!"
+        from users
+        join active on active.user_id = users.id
+        left join holdings on holdings.user_id = users.id
+        where coalesce(holdings.total, 0) < $4::bigint
+          and (users.suspended_at is null or users.suspended_until <= now())
+          and not exists (
+            select 1 from allowances
+            where allowances.user_id = users.id and allowances.period_start = $2
+          )
+      "#,
+      active_since,
+      period_start,
+      i64::from(allowance.amount),
+      i64::from(allowance.cap),
+    )
+    .fetch_all(&self.backend.pool)
+    .await
+  }
+
+  ///
+  /// Pays the allowance of the period starting at given time to given recipient, by minting
+  /// the amount and offering it from the mint to them. The payment is claimed beforehand,
+  /// so that no recipient is paid twice for the same period.
+  ///
+  /// ```
+  /// ──▷ :mint ══▷ :mint ──▷ mint:a
+  /// ```
+  ///
+  /// ### Returns:
+  /// the offer paying the allowance, or `None` if the recipient is already paid for the period.
+  ///
+  pub async fn pay_allowance(
+    &self,
+    recipient: &AllowanceRecipient,
+    period_start: DateTime<Utc>,
+  ) -> Result<Option<Transaction>, WalletError> {
+    // allowances are paid by a scheduled job, on nobody's behalf. issuing them on
+    // behalf of recipients would, for example, count them as activity of recipients.
+    let issuer = self.as_system().await.map_err(|err| {
+      error!("Failed to fetch the system user: {err:?}");
+      WalletError::Unknown
+    })?;
+
+    let claimed = sqlx::query!(
+      "
+        insert into allowances (user_id, period_start, amount) values ($1, $2, $3)
+        on conflict do nothing
+      ",
+      recipient.id,
+      period_start,
+      recipient.amount,
+    )
+    .execute(&self.backend.pool)
+    .await
+    .map_err(|err| {
+      error!("Failed to claim allowance of {}: {err:?}", recipient.email);
+      WalletError::Unknown
+    })?;
+
+    if claimed.rows_affected() == 0 {
+      return Ok(None);
+    }
+
+    let amount = u32::try_from(recipient.amount).unwrap_or_default();
+    let note = format!("allowance for {}", period_start.format("%Y-%m-%d"));
+
+    let paid = match self.mint(amount, Some(note.clone()), &issuer).await {
+      Ok(_) => {
+        self
+          .offer_from_balance(
+            &Account::mint(),
+            &Account::of_user(&recipient.id),
+            amount,
+            Some(note),
+            None,
+            &issuer,
+          )
+          .await
+      }
+      Err(err) => Err(err),
+    };
+
+    match paid {
+      Ok(result) => {
+        if let Err(err) = sqlx::query!(
+          "update allowances set tx = $3 where user_id = $1 and period_start = $2",
+          recipient.id,
+          period_start,
+          result.offer.id,
+        )
+        .execute(&self.backend.pool)
+        .await
+        {
+          error!("Failed to link allowance of {}: {err:?}", recipient.email);
+        }
+
+        Ok(Some(result.offer))
+      }
+      Err(err) => {
+        // releasing the claim allows paying the allowance on the next run.
+        // coins minted but not offered remain in the mint.
+        if let Err(err) = sqlx::query!(
+          "delete from allowances where user_id = $1 and period_start = $2 and tx is null",
+          recipient.id,
+          period_start,
+        )
+        .execute(&self.backend.pool)
+        .await
+        {
+          error!(
+            "Failed to release allowance of {}: {err:?}",
+            recipient.email
+          );
+        }
+
+        Err(err)
+      }
+    }
+  }
+}
@@ -1,15 +1,26 @@
 use axum::{
   extract::{Extension, Json, Path, Query},
+  http::{header, HeaderMap},
   response::IntoResponse,
 };
-use serde::Deserialize;
+use chrono::{DateTime, Duration, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use sqlx::types::Uuid;
 
-use super::super::auth::{admin::AdminUser, AuthenticatedUser};
+use super::super::auth::{
+  admin::AdminUser, audit::AuditLog, step_up::StepUps, ActiveUser, AuthenticatedUser,
+};
 use super::account::Account;
 use super::auth::{UsableInboundOffer, UsableOutgoingOffer};
+use super::economy;
 use super::error::WalletError;
+use super::graph::{GraphRoot, TransactionGraph};
+use super::history::{self, EntryKind, HistoryEntry, HistoryFilter};
 use super::ledger::Ledger;
+use super::limits::{self, LimitSetter, Limits};
+use super::recipient::{Recipient, RecipientLookups};
 
 pub async fn balance(
   Extension(ledger): Extension<Ledger>,
@@ -76,6 +87,138 @@ pub async fn history(
     .map_err(|_| WalletError::Unknown)
 }
 
+const MAX_HISTORY_PAGE: u32 = 100;
+const MAX_HISTORY_EXPORT: u32 = 10_000;
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+  pub since: Option<DateTime<Utc>>,
+  pub until: Option<DateTime<Utc>>,
+  pub counterparty: Option<Uuid>,
+  pub counterparty_sys: Option<String>,
+  pub kind: Option<EntryKind>,
+  pub cursor: Option<Uuid>,
+  pub limit: Option<u32>,
+  #[serde(default)]
+  pub format: ExportFormat,
+}
+
+impl HistoryQuery {
+  fn filter(&self) -> HistoryFilter {
+    HistoryFilter {
+      since: self.since,
+      until: self.until,
+      counterparty: self.counterparty,
+      counterparty_sys: self.counterparty_sys.clone(),
+      kind: self.kind,
+    }
+  }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+  #[default]
+  Csv,
+  Json,
+}
+
+#[derive(Serialize)]
+pub struct HistoryPage {
+  pub entries: Vec<HistoryEntry>,
+  pub next_cursor: Option<Uuid>,
+}
+
+///
+/// Returns the history of the authenticated user, classified from their perspective
+/// and filtered by given query. Pass `next_cursor` of a page as `cursor` to get the next page.
+///
+pub async fn history_entries(
+  Extension(ledger): Extension<Ledger>,
+  user: AuthenticatedUser,
+  Query(query): Query<HistoryQuery>,
+) -> Result<impl IntoResponse, WalletError> {
+  let limit = query.limit.unwrap_or(32).min(MAX_HISTORY_PAGE);
+
+  let entries = ledger
+    .history_of(&user.id, &query.filter(), query.cursor, limit)
+    .await
+    .map_err(|err| {
+      error!("Failed to fetch history of {}: {err:?}", user.email);
+      WalletError::Unknown
+    })?;
+
+  let next_cursor = if entries.len() == limit as usize {
+    entries.last().map(|entry| entry.id)
+  } else {
+    None
+  };
+
+  Ok(Json(HistoryPage {
+    entries,
+    next_cursor,
+  }))
+}
+
+///
+/// Exports the (filtered) history of the authenticated user as CSV or JSON,
+/// for reconciling their spending. Exports are capped at 10k entries, narrow
+/// down the date range for longer histories.
+///
+pub async fn export_history(
+  Extension(ledger): Extension<Ledger>,
+  user: AuthenticatedUser,
+  Query(query): Query<HistoryQuery>,
+) -> Result<impl IntoResponse, WalletError> {
+  let entries = ledger
+    .history_of(&user.id, &query.filter(), query.cursor, MAX_HISTORY_EXPORT)
+    .await
+    .map_err(|err| {
+      error!("Failed to export history of {}: {err:?}", user.email);
+      WalletError::Unknown
+    })?;
+
+  let (content_type, filename, body) = match query.format {
+    ExportFormat::Csv => ("text/csv", "history.csv", history::to_csv(&entries)),
+    ExportFormat::Json => (
+      "application/json",
+      "history.json",
+      serde_json::to_string(&entries).map_err(|_| WalletError::Unknown)?,
+    ),
+  };
+
+  Ok((
+    [
+      (header::CONTENT_TYPE, content_type.to_string()),
+      (
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{filename}\""),
+      ),
+    ],
+    body,
+  ))
+}
+
+///
+/// Requires the request to carry a step-up confirmation of given operation, if
+/// offering given amount needs one (see `Config::requires_step_up()`).
+///
+async fn confirm_step_up(
+  step_ups: &StepUps,
+  headers: &HeaderMap,
+  user: &AuthenticatedUser,
+  operation: &Value,
+) -> Result<(), WalletError> {
+  match step_ups.verify(headers, user, operation).await {
+    Ok(true) => Ok(()),
+    Ok(false) => Err(WalletError::StepUpRequired),
+    Err(err) => {
+      error!("Failed to verify step-up of {}: {err:?}", user.id);
+      Err(WalletError::Unknown)
+    }
+  }
+}
+
 #[derive(Deserialize)]
 pub struct OfferBody {
   pub amount: u32,
@@ -83,17 +226,42 @@ pub struct OfferBody {
   pub note: Option<String>,
 }
 
+///
+/// Offers given amount from the balance of the authenticated user to a system account
+/// (e.g. a tile, when bidding). Offers taking the user's spending of the day above the
+/// step-up threshold need a step-up confirmation of
+/// `{ "action": "wallet.offer", "receiver_sys": ..., "amount": ..., "note": ... }`.
+///
 pub async fn offer(
   Extension(ledger): Extension<Ledger>,
-  user: AuthenticatedUser,
+  Extension(step_ups): Extension<StepUps>,
+  ActiveUser(user): ActiveUser,
+  headers: HeaderMap,
   Json(body): Json<OfferBody>,
 ) -> Result<impl IntoResponse, WalletError> {
+  let sender = Account::of_user(&user.id);
+
+  // the confirmation is only used up once the offer is known to go through.
+  if ledger
+    .check_offer_from_balance(&sender, body.amount, &user)
+    .await?
+  {
+    let operation = json!({
+      "action": "wallet.offer",
+      "receiver_sys": body.receiver_sys,
+      "amount": body.amount,
+      "note": body.note,
+    });
+    confirm_step_up(&step_ups, &headers, &user, &operation).await?;
+  }
+
   match ledger
     .offer_from_balance(
-      &Account::of_user(&user.id),
+      &sender,
       &Account::of_sys_user(&body.receiver_sys),
       body.amount,
       body.note,
+      None,
       &user,
     )
     .await
@@ -103,7 +271,101 @@ pub async fn offer(
   }
 }
 
+const MAX_NOTE_LENGTH: usize = 255;
+
 #[derive(Deserialize)]
+pub struct SendBody {
+  pub recipient: Recipient,
+  pub amount: u32,
+  pub note: Option<String>,
+  pub expires_at: Option<DateTime<Utc>>,
+}
+
+///
+/// Sends coins to another user, by offering given amount from the balance of
+/// the authenticated user to the recipient. The recipient can then accept (or reject)
+/// the offer like any other. Users can only send so much to others per (UTC) day. If `expires_at`
+/// is given, the offer is returned to the user if the recipient doesn't accept it by then.
+/// Users can only look up so many recipients by email per hour. Offers taking the user's
+/// spending of the day above the step-up threshold need a step-up confirmation of
+/// `{ "action": "wallet.send", "recipient": ..., "amount": ..., "note": ..., "expires_at": ... }`,
+/// where `expires_at` is in milliseconds since the unix epoch (or `null`).
+///
+pub async fn send(
+  Extension(ledger): Extension<Ledger>,
+  Extension(step_ups): Extension<StepUps>,
+  Extension(lookups): Extension<RecipientLookups>,
+  ActiveUser(user): ActiveUser,
+  headers: HeaderMap,
+  Json(body): Json<SendBody>,
+) -> Result<impl IntoResponse, WalletError> {
+  if body
+    .note
+    .as_ref()
+    .is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH)
+    || body.expires_at.is_some_and(|at| at <= Utc::now())
+  {
+    return Err(WalletError::ErroneousTransaction);
+  }
+
+  if !lookups.allows(&user.id, &body.recipient) {
+    return Err(WalletError::LimitExceeded { resets_at: None });
+  }
+
+  let recipient = match ledger.find_recipient(&body.recipient).await {
+    Ok(Some(recipient)) => recipient,
+    Ok(None) => return Err(WalletError::RecipientNotFound),
+    Err(err) => {
+      error!("Failed to find recipient {:?}: {err:?}", body.recipient);
+      return Err(WalletError::Unknown);
+    }
+  };
+  if recipient == user.id {
+    return Err(WalletError::ErroneousTransaction);
+  }
+
+  let (today, tomorrow) = limits::day_of(Utc::now());
+  let sent = ledger
+    .sent_to_users_since(&user.id, today)
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+  if sent + i64::from(body.amount) > i64::from(ledger.config.daily_send_limit) {
+    return Err(WalletError::LimitExceeded {
+      resets_at: Some(tomorrow),
+    });
+  }
+
+  let sender = Account::of_user(&user.id);
+
+  // the confirmation is only used up once the offer is known to go through.
+  if ledger
+    .check_offer_from_balance(&sender, body.amount, &user)
+    .await?
+  {
+    let operation = json!({
+      "action": "wallet.send",
+      "recipient": body.recipient,
+      "amount": body.amount,
+      "note": body.note,
+      "expires_at": body.expires_at.map(|at| at.timestamp_millis()),
+    });
+    confirm_step_up(&step_ups, &headers, &user, &operation).await?;
+  }
+
+  ledger
+    .offer_from_balance(
+      &sender,
+      &Account::of_user(&recipient),
+      body.amount,
+      body.note,
+      body.expires_at,
+      &user,
+    )
+    .await
+    .map(Json)
+}
+
+#[derive(Deserialize, Serialize)]
 pub struct InjectBody {
   pub amount: u32,
   pub receiver: Account,
@@ -112,23 +374,37 @@ pub struct InjectBody {
 
 pub async fn inject(
   Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
   AdminUser(user): AdminUser,
   Json(body): Json<InjectBody>,
 ) -> Result<impl IntoResponse, WalletError> {
-  match ledger
-    .inject(&body.receiver, body.amount, body.note, &user)
+  audit
+    .record(
+      &user,
+      "wallet.inject",
+      Some(body.receiver.to_string()),
+      &body,
+    )
     .await
-  {
-    Ok(result) => Ok(Json(result)),
-    Err(err) => Err(err),
-  }
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger
+    .inject(&body.receiver, body.amount, body.note.clone(), &user)
+    .await
+    .map(Json)
 }
 
 pub async fn user_balance(
   Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
   Path(id): Path<Uuid>,
-  AdminUser(_): AdminUser,
+  AdminUser(user): AdminUser,
 ) -> Result<impl IntoResponse, WalletError> {
+  audit
+    .record(&user, "wallet.user_balance", Some(id.to_string()), &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
   ledger
     .find_balance(&Account::of_user(&id))
     .await
@@ -136,7 +412,83 @@ pub async fn user_balance(
     .map_err(|_| WalletError::TransactionNotFound)
 }
 
-#[derive(Deserialize)]
+///
+/// Returns the spending limits of the authenticated user, and how much
+/// they have spent today (see `Ledger::spending_status()`).
+///
+pub async fn limits(
+  Extension(ledger): Extension<Ledger>,
+  user: AuthenticatedUser,
+) -> Result<impl IntoResponse, WalletError> {
+  ledger
+    .spending_status(&user.id, None)
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to check spending of {}: {err:?}", user.id);
+      WalletError::Unknown
+    })
+}
+
+///
+/// Sets the spending limits of the authenticated user. Users can only lower the limits
+/// in effect for them, so limits above those are ignored (and zero or `null` unsets them).
+///
+pub async fn set_limits(
+  Extension(ledger): Extension<Ledger>,
+  ActiveUser(user): ActiveUser,
+  Json(body): Json<Limits>,
+) -> Result<impl IntoResponse, WalletError> {
+  ledger
+    .set_spending_limits(&user.id, LimitSetter::User, body)
+    .await
+    .map(Json)
+}
+
+pub async fn user_limits(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  Path(id): Path<Uuid>,
+  AdminUser(user): AdminUser,
+) -> Result<impl IntoResponse, WalletError> {
+  audit
+    .record(&user, "wallet.limits", Some(id.to_string()), &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger
+    .spending_status(&id, None)
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to check spending of {id}: {err:?}");
+      WalletError::Unknown
+    })
+}
+
+///
+/// Overrides the configured spending limits of given user (zero meaning unlimited,
+/// and `null` falling back to the configured limit).
+///
+pub async fn set_user_limits(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  Path(id): Path<Uuid>,
+  AdminUser(user): AdminUser,
+  Json(body): Json<Limits>,
+) -> Result<impl IntoResponse, WalletError> {
+  audit
+    .record(&user, "wallet.set_limits", Some(id.to_string()), &body)
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger
+    .set_spending_limits(&id, LimitSetter::Admin, body)
+    .await
+    .map(Json)
+}
+
+#[derive(Deserialize, Serialize)]
 pub struct PartiallyAcceptBody {
   pub offer: Uuid,
   pub amount: u32,
@@ -145,6 +497,7 @@ pub struct PartiallyAcceptBody {
 
 pub async fn partially_accept(
   Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
   AdminUser(user): AdminUser,
   Json(body): Json<PartiallyAcceptBody>,
 ) -> Result<impl IntoResponse, WalletError> {
@@ -161,11 +514,261 @@ pub async fn partially_accept(
     return Err(WalletError::UnauthorizedTransaction);
   }
 
-  match ledger
-    .partially_accept_offer(&offer, body.amount, body.note, &user)
+  audit
+    .record(
+      &user,
+      "wallet.partially_accept",
+      Some(body.offer.to_string()),
+      &body,
+    )
     .await
-  {
-    Ok(result) => Ok(Json(result)),
-    Err(err) => Err(err),
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger
+    .partially_accept_offer(&offer, body.amount, body.note.clone(), &user)
+    .await
+    .map(Json)
+}
+
+///
+/// Verifies the integrity of the ledger (see `Ledger::verify()`).
+///
+pub async fn verify(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  AdminUser(user): AdminUser,
+) -> Result<impl IntoResponse, WalletError> {
+  audit
+    .record(&user, "wallet.verify", None, &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger.verify().await.map(Json).map_err(|err| {
+    error!("Failed to verify ledger: {err:?}");
+    WalletError::Unknown
+  })
+}
+
+///
+/// Returns the totals of the mint account (see `Ledger::mint_summary()`).
+///
+pub async fn mint(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  AdminUser(user): AdminUser,
+) -> Result<impl IntoResponse, WalletError> {
+  audit
+    .record(&user, "wallet.mint", None, &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger.mint_summary().await.map(Json).map_err(|err| {
+    error!("Failed to summarise mint: {err:?}");
+    WalletError::Unknown
+  })
+}
+
+///
+/// Returns the current totals of the economy (see `Ledger::economy_at()`).
+///
+pub async fn economy(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  AdminUser(user): AdminUser,
+) -> Result<impl IntoResponse, WalletError> {
+  audit
+    .record(&user, "wallet.economy", None, &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  match ledger.economy_at(&[Utc::now()]).await {
+    Ok(mut totals) => totals.pop().map(Json).ok_or(WalletError::Unknown),
+    Err(err) => {
+      error!("Failed to fetch economy totals: {err:?}");
+      Err(WalletError::Unknown)
+    }
   }
 }
+
+#[derive(Deserialize)]
+pub struct SeriesQuery {
+  pub since: Option<DateTime<Utc>>,
+  pub until: Option<DateTime<Utc>>,
+  #[serde(default, with = "humantime_serde")]
+  pub interval: Option<std::time::Duration>,
+}
+
+///
+/// Each point of an economy series is computed by scanning the ledger, so series
+/// requested over HTTP are kept short. Longer series are available via reports.
+///
+const MAX_ECONOMY_SERIES_POINTS: usize = 60;
+
+///
+/// Returns a time series of the totals of the economy. Defaults to daily
+/// totals of the last 30 days.
+///
+pub async fn economy_series(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  AdminUser(user): AdminUser,
+  Query(query): Query<SeriesQuery>,
+) -> Result<impl IntoResponse, WalletError> {
+  let interval = match query.interval {
+    Some(interval) => {
+      Duration::from_std(interval).map_err(|_| WalletError::ErroneousTransaction)?
+    }
+    None => Duration::days(1),
+  };
+  let until = query.until.unwrap_or_else(Utc::now);
+  let since = match query.since {
+    Some(since) => since,
+    None => interval
+      .checked_mul(30)
+      .and_then(|span| until.checked_sub_signed(span))
+      .ok_or(WalletError::ErroneousTransaction)?,
+  };
+
+  let points = economy::series_points(since, until, interval, MAX_ECONOMY_SERIES_POINTS)
+    .ok_or(WalletError::ErroneousTransaction)?;
+
+  audit
+    .record(&user, "wallet.economy_series", None, &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger.economy_at(&points).await.map(Json).map_err(|err| {
+    error!("Failed to fetch economy series: {err:?}");
+    WalletError::Unknown
+  })
+}
+
+#[derive(Deserialize)]
+pub struct SystemAccountsQuery {
+  pub prefix: Option<String>,
+  pub offset: Option<u32>,
+  pub limit: Option<u32>,
+}
+
+pub async fn system_accounts(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  AdminUser(user): AdminUser,
+  Query(query): Query<SystemAccountsQuery>,
+) -> Result<impl IntoResponse, WalletError> {
+  audit
+    .record(&user, "wallet.system_accounts", None, &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger
+    .system_accounts(
+      query.prefix.as_deref(),
+      query.offset.unwrap_or(0),
+      query.limit.unwrap_or(32),
+    )
+    .await
+    .map(Json)
+    .map_err(|err| {
+      error!("Failed to fetch system accounts: {err:?}");
+      WalletError::Unknown
+    })
+}
+
+pub async fn system_balance(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  Path(name): Path<String>,
+  AdminUser(user): AdminUser,
+) -> Result<impl IntoResponse, WalletError> {
+  audit
+    .record(&user, "wallet.system_balance", Some(name.clone()), &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  ledger
+    .find_balance(&Account::System(name))
+    .await
+    .map(Json)
+    .map_err(|_| WalletError::TransactionNotFound)
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+  #[default]
+  Dot,
+  Graphml,
+}
+
+///
+/// Root of the graph to export: exactly one of `tx`, `user` or `sys` must be given.
+///
+#[derive(Deserialize)]
+pub struct GraphQuery {
+  pub tx: Option<Uuid>,
+  pub user: Option<Uuid>,
+  pub sys: Option<String>,
+  pub depth: Option<u32>,
+  #[serde(default)]
+  pub format: GraphFormat,
+}
+
+///
+/// Exports the transaction subgraph around a transaction or an account as Graphviz DOT
+/// or `GraphML` (see `Ledger::transaction_graph()`), for debugging funds. Depth defaults to 4.
+/// The `x-graph-truncated` header indicates whether the graph hit the size limit.
+///
+pub async fn graph(
+  Extension(ledger): Extension<Ledger>,
+  Extension(audit): Extension<AuditLog>,
+  AdminUser(user): AdminUser,
+  Query(query): Query<GraphQuery>,
+) -> Result<impl IntoResponse, WalletError> {
+  let root = match (query.tx, query.user, query.sys) {
+    (Some(tx), None, None) => GraphRoot::Transaction(tx),
+    (None, Some(id), None) => GraphRoot::Account(Account::of_user(&id)),
+    (None, None, Some(sys)) => GraphRoot::Account(Account::System(sys)),
+    _ => return Err(WalletError::ErroneousTransaction),
+  };
+
+  audit
+    .record(&user, "wallet.graph", Some(format!("{root:?}")), &())
+    .await
+    .map_err(|_| WalletError::Unknown)?;
+
+  let graph: TransactionGraph = ledger
+    .transaction_graph(&root, query.depth.unwrap_or(4))
+    .await
+    .map_err(|err| match err {
+      sqlx::Error::RowNotFound => WalletError::TransactionNotFound,
+      err => {
+        error!("Failed to export transaction graph of {root:?}: {err:?}");
+        WalletError::Unknown
+      }
+    })?;
+
+  let (content_type, filename, body) = match query.format {
+    GraphFormat::Dot => ("text/vnd.graphviz", "ledger.dot", graph.to_dot()),
+    GraphFormat::Graphml => (
+      "application/graphml+xml",
+      "ledger.graphml",
+      graph.to_graphml(),
+    ),
+  };
+
+  Ok((
+    [
+      (header::CONTENT_TYPE, content_type.to_string()),
+      (
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{filename}\""),
+      ),
+      (
+        header::HeaderName::from_static("x-graph-truncated"),
+        graph.truncated.to_string(),
+      ),
+    ],
+    body,
+  ))
+}
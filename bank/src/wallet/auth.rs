@@ -4,7 +4,7 @@ use axum::{
   response::{IntoResponse, Response},
 };
 
-use super::super::auth::AuthenticatedUser;
+use super::super::auth::{ActiveUser, AuthenticatedUser};
 use super::error::WalletError;
 use super::ledger::Ledger;
 use super::transaction::Transaction;
@@ -26,7 +26,7 @@ where
     let Json(rtx) = Json::<Transaction>::from_bytes(bytes.as_ref())
       .map_err(|_| WalletError::TransactionNotFound.into_response())?;
 
-    let user = AuthenticatedUser::from_request_parts(&mut parts, state)
+    let ActiveUser(user) = ActiveUser::from_request_parts(&mut parts, state)
       .await
       .map_err(IntoResponse::into_response)?;
 
@@ -1,6 +1,35 @@
 use serde::Deserialize;
 
+use super::allowance::Allowance;
+use super::limits::SpendingLimits;
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Config {
   pub initial_balance: u32,
+  /// How much a user can send to other users per day (defaults to 100).
+  #[serde(default = "default_daily_send_limit")]
+  pub daily_send_limit: u32,
+  #[serde(default)]
+  pub allowance: Allowance,
+  #[serde(default)]
+  pub spending: SpendingLimits,
+  /// Offers taking a user's spending of the day above this amount need a
+  /// passkey confirmation (zero disables it).
+  #[serde(default)]
+  pub step_up_threshold: u32,
+}
+
+impl Config {
+  ///
+  /// Whether offering given amount, on top of what the user has already spent
+  /// today, needs a step-up confirmation (see `auth::step_up`). Spending is
+  /// counted cumulatively, so that large amounts can't be split into small offers.
+  ///
+  pub fn requires_step_up(&self, spent: i64, amount: u32) -> bool {
+    self.step_up_threshold > 0 && spent + i64::from(amount) > i64::from(self.step_up_threshold)
+  }
+}
+
+fn default_daily_send_limit() -> u32 {
+  100
 }
@@ -0,0 +1,177 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use super::ledger::Ledger;
+
+///
+/// The maximum number of points a time series of the economy can have, when
+/// generated by reports. Series requested over HTTP are capped much lower.
+///
+pub const MAX_SERIES_POINTS: usize = 366;
+
+///
+/// Totals of the economy at some point in time, i.e. the coins held by
+/// live (unused) transactions at that time, split by who holds them:
+/// - `supply`: all coins in existence, which is the sum of all the others,
+/// - `user_balances`: coins in balances of users,
+/// - `open_offers`: coins locked in offers to users, not yet accepted or rejected,
+/// - `tile_holdings`: coins held by tile accounts, i.e. locked in pending bids,
+/// - `bank_revenue`: coins collected by the bank (from published bids),
+/// - `mint_reserve`: coins minted but not yet offered by the mint,
+/// - `other`: coins held by other system accounts.
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct EconomyTotals {
+  pub at: DateTime<Utc>,
+  pub supply: i64,
+  pub user_balances: i64,
+  pub open_offers: i64,
+  pub tile_holdings: i64,
+  pub bank_revenue: i64,
+  pub mint_reserve: i64,
+  pub other: i64,
+}
+
+///
+/// A system account alongside its holdings: `balance` is the total of its live
+/// state, and `pending` is the total of open offers (or bids) to it.
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct SystemAccount {
+  pub account: String,
+  pub balance: i64,
+  pub pending: i64,
+  pub updated_at: DateTime<Utc>,
+}
+
+///
+/// Returns evenly spaced points in time from `since` to `until` (inclusive),
+/// or `None` if the range is invalid or has more than `max_points` points.
+///
+pub fn series_points(
+  since: DateTime<Utc>,
+  until: DateTime<Utc>,
+  interval: Duration,
+  max_points: usize,
+) -> Option<Vec<DateTime<Utc>>> {
+  if since > until || interval <= Duration::zero() {
+    return None;
+  }
+
+  let count = (until - since).num_seconds() / interval.num_seconds().max(1) + 1;
+  if usize::try_from(count).ok()? > max_points {
+    return None;
+  }
+
+  Some(
+    (0..i32::try_from(count).ok()?)
+      .map(|i| since + interval * i)
+      .collect(),
+  )
+}
+
+impl Ledger {
+  ///
+  /// Returns the totals of the economy (see `EconomyTotals`) at each of given points in time.
+  /// A transaction counts towards the totals at some point if it was created before it,
+  /// and wasn't consumed or merged by then.
+  ///
+  pub async fn economy_at(
+    &self,
+    points: &[DateTime<Utc>],
+  ) -> Result<Vec<EconomyTotals>, sqlx::Error> {
+    sqlx::query_as!(
+      EconomyTotals,
+      r#"
+        with points as (
+          select unnest($1::timestamptz[]) as at
+        ),
+        live as (
+          select points.at, tx.*
+          from points
+          join transactions tx on tx.created_at <= points.at
+          where not exists (
+              select 1 from transactions used
+              where used.consumes = tx.id and used.created_at <= points.at
+            )
+            and not exists (
+              select 1 from transactions used
+              where used.merges = tx.id and used.created_at <= points.at
+            )
+        )
+        select
+          points.at as "at!",
+          coalesce(sum(live.consumed_value + live.merged_value), 0)::bigint as "supply!",
+          coalesce(sum(live.consumed_value + live.merged_value)
+            filter (where live.receiver is not null and live.is_state), 0)::bigint
+            as "user_balances!",
+          coalesce(sum(live.consumed_value + live.merged_value)
+            filter (where live.receiver is not null and not live.is_state), 0)::bigint
+            as "open_offers!",
+          coalesce(sum(live.consumed_value + live.merged_value)
+            filter (where live.receiver_sys like 'tile:%'), 0)::bigint
+            as "tile_holdings!",
+          coalesce(sum(live.consumed_value + live.merged_value)
+            filter (where live.receiver_sys = 'bank'), 0)::bigint
+            as "bank_revenue!",
+          coalesce(sum(live.consumed_value + live.merged_value)
+            filter (where live.receiver_sys = 'mint'), 0)::bigint
+            as "mint_reserve!",
+          coalesce(sum(live.consumed_value + live.merged_value)
+            filter (
+              where live.receiver_sys is not null
+                and live.receiver_sys not like 'tile:%'
+                and live.receiver_sys not in ('bank', 'mint')
+            ), 0)::bigint
+            as "other!"
+        from points
+        left join live on live.at = points.at
+        group by points.at
+        order by points.at
+      "#,
+      points,
+    )
+    .fetch_all(&self.backend.pool)
+    .await
+  }
+
+  ///
+  /// Returns system accounts holding coins (or with a live state), alongside their holdings.
+  ///
+  /// ### Params:
+  /// - `prefix`: optional, only returns accounts starting with this prefix (e.g. `tile:`)
+  /// - `offset`: the number of accounts to skip
+  /// - `limit`: the maximum number of accounts to return
+  ///
+  pub async fn system_accounts(
+    &self,
+    prefix: Option<&str>,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<SystemAccount>, sqlx::Error> {
+    sqlx::query_as!(
+      SystemAccount,
+      r#"
+        select
+          receiver_sys as "account!",
+          coalesce(sum(consumed_value + merged_value) filter (where is_state), 0)::bigint
+            as "balance!",
+          coalesce(sum(consumed_value + merged_value) filter (where not is_state), 0)::bigint
+            as "pending!",
+          max(created_at) as "updated_at!"
+        from transactions
+        where receiver_sys is not null and consumed is false and merged is false
+          and ($1::text is null or starts_with(receiver_sys, $1))
+        group by receiver_sys
+        order by receiver_sys
+        offset $2
+        limit $3
+      "#,
+      prefix,
+      i64::from(offset),
+      i64::from(limit),
+    )
+    .fetch_all(&self.backend.pool)
+    .await
+  }
+}
@@ -2,6 +2,7 @@ use axum::{
   http::StatusCode,
   response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -18,10 +19,30 @@ pub enum WalletError {
   UnauthorizedTransaction,
   #[error("Erroneous transaction")]
   ErroneousTransaction,
+  #[error("Recipient not found")]
+  RecipientNotFound,
+  #[error("Limit exceeded")]
+  LimitExceeded {
+    // when the exceeded limit resets, if it does.
+    resets_at: Option<DateTime<Utc>>,
+  },
+  #[error("Step-up confirmation required")]
+  StepUpRequired,
 }
 
 impl IntoResponse for WalletError {
   fn into_response(self) -> Response {
+    if let WalletError::LimitExceeded {
+      resets_at: Some(resets_at),
+    } = self
+    {
+      return (
+        StatusCode::FORBIDDEN,
+        format!("Limit exceeded, resets at {}", resets_at.to_rfc3339()),
+      )
+        .into_response();
+    }
+
     (match self {
       WalletError::Unknown => (StatusCode::INTERNAL_SERVER_ERROR, "Unknown error"),
       WalletError::InsufficientFunds => (StatusCode::FORBIDDEN, "Insufficient funds"),
@@ -31,6 +52,9 @@ impl IntoResponse for WalletError {
       WalletError::AlreadyUsedTransaction => (StatusCode::CONFLICT, "Transaction already used"),
       WalletError::TransactionNotFound => (StatusCode::NOT_FOUND, "Transaction not found"),
       WalletError::ErroneousTransaction => (StatusCode::BAD_REQUEST, "Erroneous transaction"),
+      WalletError::RecipientNotFound => (StatusCode::NOT_FOUND, "Recipient not found"),
+      WalletError::LimitExceeded { .. } => (StatusCode::FORBIDDEN, "Limit exceeded"),
+      WalletError::StepUpRequired => (StatusCode::FORBIDDEN, "Step-up confirmation required"),
     })
     .into_response()
   }
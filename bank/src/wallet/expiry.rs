@@ -0,0 +1,53 @@
+use log::error;
+
+use super::error::WalletError;
+use super::ledger::Ledger;
+use super::transaction::Transaction;
+
+impl Ledger {
+  ///
+  /// Returns offers that have expired without being accepted (or otherwise used),
+  /// earliest expiries first. Bids (offers to tile accounts) are excluded, as they
+  /// are handled by the bidding policy.
+  ///
+  pub async fn find_expired_offers(&self, limit: u32) -> Result<Vec<Transaction>, sqlx::Error> {
+    sqlx::query_as!(
+      Transaction,
+      "
+        select transactions.* from offer_expiries
+        join transactions on transactions.id = offer_expiries.tx
+        where offer_expiries.expires_at <= now()
+          and transactions.consumed is false and transactions.merged is false
+          and transactions.receiver is not null
+        order by offer_expiries.expires_at
+        limit $1
+      ",
+      i64::from(limit),
+    )
+    .fetch_all(&self.backend.pool)
+    .await
+  }
+
+  ///
+  /// Expires given offer, rejecting it back to its sender. The rejection is issued
+  /// on behalf of the user who issued the offer, as they set it to expire.
+  ///
+  /// ```
+  /// ──▷ a:b ──▷ b:a
+  /// ```
+  ///
+  /// ### Returns:
+  /// the reverse transaction, or `WalletError::AlreadyUsedTransaction` if the offer was
+  /// accepted (or otherwise used) meanwhile.
+  ///
+  pub async fn expire_offer(&self, offer: &Transaction) -> Result<Transaction, WalletError> {
+    let issuer = self.on_behalf_of(&offer.issued_by).await.map_err(|err| {
+      error!("Failed to fetch issuer of offer {:?}: {err:?}", offer.id);
+      WalletError::Unknown
+    })?;
+
+    self
+      .reject_offer(offer, Some("offer expired".to_string()), &issuer)
+      .await
+  }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use sqlx::types::Uuid;
+
+use super::account::Account;
+use super::ledger::Ledger;
+use super::transaction::Transaction;
+
+///
+/// The maximum depth a transaction graph can be explored to.
+///
+pub const MAX_GRAPH_DEPTH: u32 = 16;
+
+///
+/// The maximum number of transactions a transaction graph can hold. Exploration
+/// stops (and the graph is marked as truncated) when this is reached.
+///
+pub const MAX_GRAPH_NODES: usize = 1024;
+
+///
+/// Where to start exploring a transaction graph from: a given transaction,
+/// or the live (unused) transactions of an account, i.e. its balance and its
+/// open offers (incoming and outgoing).
+///
+#[derive(Debug, Clone)]
+pub enum GraphRoot {
+  Transaction(Uuid),
+  Account(Account),
+}
+
+///
+/// A subgraph of the ledger, where transactions are nodes and edges connect
+/// transactions to the ones consuming or merging them. `truncated` indicates
+/// that exploration stopped due to the size limit, before reaching given depth.
+///
+#[derive(Debug, Default)]
+pub struct TransactionGraph {
+  pub nodes: Vec<Transaction>,
+  pub truncated: bool,
+}
+
+///
+/// An edge of a transaction graph, from a used transaction to the transaction using it.
+///
+struct Edge<'a> {
+  from: &'a Uuid,
+  to: &'a Uuid,
+  merged: bool,
+  value: i32,
+}
+
+impl TransactionGraph {
+  fn ids(&self) -> HashSet<Uuid> {
+    self.nodes.iter().filter_map(|node| node.id).collect()
+  }
+
+  fn edges(&self) -> Vec<Edge<'_>> {
+    let ids = self.ids();
+    let mut edges = vec![];
+
+    for node in &self.nodes {
+      let Some(id) = node.id.as_ref() else {
+        continue;
+      };
+
+      if let Some(consumes) = node.consumes.as_ref().filter(|used| ids.contains(used)) {
+        edges.push(Edge {
+          from: consumes,
+          to: id,
+          merged: false,
+          value: node.consumed_value,
+        });
+      }
+
+      if let Some(merges) = node.merges.as_ref().filter(|used| ids.contains(used)) {
+        edges.push(Edge {
+          from: merges,
+          to: id,
+          merged: true,
+          value: node.merged_value,
+        });
+      }
+    }
+
+    edges
+  }
+
+  ///
+  /// Renders the graph in Graphviz DOT format. Value flows along the edges, consumed
+  /// edges are drawn as single lines and merged edges as double lines (like `──▷` and `══▷`
+  /// in the diagrams of the codebase). States are drawn as boxes, offers as ellipses,
+  /// and live (unused) transactions are bold.
+  ///
+  pub fn to_dot(&self) -> String {
+    let mut dot =
+      String::from("digraph ledger {\n  rankdir=LR;\n  node [fontname=\"monospace\"];\n");
+
+    for node in &self.nodes {
+      let Some(id) = node.id else {
+        continue;
+      };
+
+      let _ = writeln!(
+        dot,
+        "  \"{id}\" [label=\"{}\", shape={}{}];",
+        dot_escape(&node.to_string()),
+        if node.is_state { "box" } else { "ellipse" },
+        if node.is_used() { "" } else { ", style=bold" },
+      );
+    }
+
+    for edge in self.edges() {
+      let _ = writeln!(
+        dot,
+        "  \"{}\" -> \"{}\" [label=\"{}\"{}];",
+        edge.from,
+        edge.to,
+        edge.value,
+        if edge.merged {
+          ", color=\"black:invis:black\""
+        } else {
+          ""
+        },
+      );
+    }
+
+    dot.push_str("}\n");
+    dot
+  }
+
+  ///
+  /// Renders the graph in `GraphML` format. Nodes carry their label, whether they are a state
+  /// and whether they are live. Edges carry whether the transaction was consumed or merged,
+  /// and the value carried along.
+  ///
+  pub fn to_graphml(&self) -> String {
+    let mut xml = String::from(concat!(
+      "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+      "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+      "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+      "  <key id=\"state\" for=\"node\" attr.name=\"is_state\" attr.type=\"boolean\"/>\n",
+      "  <key id=\"live\" for=\"node\" attr.name=\"live\" attr.type=\"boolean\"/>\n",
+      "  <key id=\"created_at\" for=\"node\" attr.name=\"created_at\" attr.type=\"string\"/>\n",
+      "  <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+      "  <key id=\"value\" for=\"edge\" attr.name=\"value\" attr.type=\"int\"/>\n",
+      "  <graph id=\"ledger\" edgedefault=\"directed\">\n",
+    ));
+
+    for node in &self.nodes {
+      let Some(id) = node.id else {
+        continue;
+      };
+
+      let _ = writeln!(
+        xml,
+        concat!(
+          "    <node id=\"{}\">",
+          "<data key=\"label\">{}</data>",
+          "<data key=\"state\">{}</data>",
+          "<data key=\"live\">{}</data>",
+          "<data key=\"created_at\">{}</data>",
+          "</node>",
+        ),
+        id,
+        xml_escape(&node.to_string()),
+        node.is_state,
+        !node.is_used(),
+        node.created_at.to_rfc3339(),
+      );
+    }
+
+    for edge in self.edges() {
+      let _ = writeln!(
+        xml,
+        concat!(
+          "    <edge source=\"{}\" target=\"{}\">",
+          "<data key=\"kind\">{}</data>",
+          "<data key=\"value\">{}</data>",
+          "</edge>",
+        ),
+        edge.from,
+        edge.to,
+        if edge.merged { "merged" } else { "consumed" },
+        edge.value,
+      );
+    }
+
+    xml.push_str("  </graph>\n</graphml>\n");
+    xml
+  }
+}
+
+fn dot_escape(value: &str) -> String {
+  value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+impl Ledger {
+  ///
+  /// Explores the subgraph of the ledger around given root, following edges in both
+  /// directions: towards the transactions each transaction consumes or merges, and towards
+  /// the transactions consuming or merging it. Each step away from the root increases depth.
+  ///
+  /// ### Params:
+  /// - `root`: where to start exploring from
+  /// - `depth`: how many steps to explore, capped at `MAX_GRAPH_DEPTH`
+  ///
+  pub async fn transaction_graph(
+    &self,
+    root: &GraphRoot,
+    depth: u32,
+  ) -> Result<TransactionGraph, sqlx::Error> {
+    let mut frontier = match root {
+      GraphRoot::Transaction(id) => vec![self.get_transaction(id).await?],
+      GraphRoot::Account(account) => {
+        let (user, sys) = match account {
+          Account::User(id) => (Some(*id), None),
+          Account::System(sys) => (None, Some(sys.clone())),
+          Account::Invalid => return Ok(TransactionGraph::default()),
+        };
+
+        sqlx::query_as!(
+          Transaction,
+          "
+            select * from transactions
+            where consumed is false and merged is false
+              and (
+                ($1::uuid is not null and (sender = $1 or receiver = $1)) or
+                ($2::text is not null and (sender_sys = $2 or receiver_sys = $2))
+              )
+            order by created_at desc
+            limit $3
+          ",
+          user,
+          sys,
+          i64::try_from(MAX_GRAPH_NODES).unwrap_or(i64::MAX),
+        )
+        .fetch_all(&self.backend.pool)
+        .await?
+      }
+    };
+
+    let mut graph = TransactionGraph::default();
+    let mut seen = HashSet::new();
+    frontier.retain(|tx| tx.id.is_some_and(|id| seen.insert(id)));
+
+    for _ in 0..depth.min(MAX_GRAPH_DEPTH) {
+      if frontier.is_empty() {
+        break;
+      }
+
+      let ids: Vec<Uuid> = frontier.iter().filter_map(|tx| tx.id).collect();
+      let used: Vec<Uuid> = frontier
+        .iter()
+        .flat_map(|tx| [tx.consumes, tx.merges])
+        .flatten()
+        .collect();
+
+      graph.nodes.append(&mut frontier);
+
+      let neighbours = sqlx::query_as!(
+        Transaction,
+        "
+          select * from transactions
+          where id = any($1) or consumes = any($2) or merges = any($2)
+        ",
+        &used,
+        &ids,
+      )
+      .fetch_all(&self.backend.pool)
+      .await?;
+
+      frontier = neighbours
+        .into_iter()
+        .filter(|tx| tx.id.is_some_and(|id| seen.insert(id)))
+        .collect();
+
+      if graph.nodes.len() + frontier.len() > MAX_GRAPH_NODES {
+        frontier.truncate(MAX_GRAPH_NODES.saturating_sub(graph.nodes.len()));
+        graph.truncated = true;
+        break;
+      }
+    }
+
+    graph.nodes.append(&mut frontier);
+
+    Ok(graph)
+  }
+}
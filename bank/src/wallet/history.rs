@@ -0,0 +1,306 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use super::account::Account;
+use super::ledger::Ledger;
+
+///
+/// What an entry of a user's history represents, from the user's perspective:
+/// - `initial`: initialization of the user's account,
+/// - `offer`: an offer made by the user to another user (or by another user to the user),
+/// - `accept`: the user accepting an offer, merging it into their balance,
+/// - `reject`: the user rejecting an offer, returning it to its sender,
+/// - `rescind`: the user rescinding an offer they made, merging it back into their balance,
+/// - `bid`: an offer made by the user to a tile,
+/// - `refund`: a bid or an offer of the user returning to them (rescinded or rejected),
+/// - `injection`: coins injected (or minted) into the user's account.
+///
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+  Initial,
+  Offer,
+  Accept,
+  Reject,
+  Rescind,
+  Bid,
+  Refund,
+  Injection,
+}
+
+impl EntryKind {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      EntryKind::Initial => "initial",
+      EntryKind::Offer => "offer",
+      EntryKind::Accept => "accept",
+      EntryKind::Reject => "reject",
+      EntryKind::Rescind => "rescind",
+      EntryKind::Bid => "bid",
+      EntryKind::Refund => "refund",
+      EntryKind::Injection => "injection",
+    }
+  }
+
+  fn parse(kind: &str) -> Self {
+    match kind {
+      "initial" => EntryKind::Initial,
+      "accept" => EntryKind::Accept,
+      "reject" => EntryKind::Reject,
+      "rescind" => EntryKind::Rescind,
+      "bid" => EntryKind::Bid,
+      "refund" => EntryKind::Refund,
+      "injection" => EntryKind::Injection,
+      _ => EntryKind::Offer,
+    }
+  }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+  In,
+  Out,
+}
+
+impl Direction {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Direction::In => "in",
+      Direction::Out => "out",
+    }
+  }
+}
+
+///
+/// An entry of a user's history, i.e. a transaction classified from the user's
+/// perspective. `balance` is the user's balance right after the entry, which only
+/// changes when coins leave or are merged into their balance (incoming offers
+/// don't change the balance until they are accepted).
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct HistoryEntry {
+  pub id: Uuid,
+  pub kind: EntryKind,
+  pub direction: Direction,
+  pub amount: i32,
+  pub balance: Option<i32>,
+  pub counterparty: Option<Account>,
+  pub note: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+///
+/// Filters for querying a user's history. All filters are optional,
+/// and are combined when provided.
+///
+#[derive(Debug, Default)]
+pub struct HistoryFilter {
+  pub since: Option<DateTime<Utc>>,
+  pub until: Option<DateTime<Utc>>,
+  pub counterparty: Option<Uuid>,
+  pub counterparty_sys: Option<String>,
+  pub kind: Option<EntryKind>,
+}
+
+struct HistoryRow {
+  id: Uuid,
+  kind: String,
+  direction: String,
+  amount: i32,
+  balance: Option<i32>,
+  counterparty: Option<Uuid>,
+  counterparty_sys: Option<String>,
+  note: Option<String>,
+  created_at: DateTime<Utc>,
+}
+
+impl From<HistoryRow> for HistoryEntry {
+  fn from(row: HistoryRow) -> Self {
+    let counterparty =
+      match Account::from_tuple(row.counterparty.as_ref(), row.counterparty_sys.as_ref()) {
+        Account::Invalid => None,
+        account => Some(account),
+      };
+
+    Self {
+      id: row.id,
+      kind: EntryKind::parse(&row.kind),
+      direction: if row.direction == "out" {
+        Direction::Out
+      } else {
+        Direction::In
+      },
+      amount: row.amount,
+      balance: row.balance,
+      counterparty,
+      note: row.note,
+      created_at: row.created_at,
+    }
+  }
+}
+
+impl Ledger {
+  ///
+  /// Returns the history of given user, most recent entries first. States resulting from
+  /// the user spending some of their balance aren't listed separately, and are instead
+  /// reflected in the balance of the entry spending it.
+  ///
+  /// ### Params:
+  /// - `user_id`: the user whose history to return
+  /// - `filter`: filters to apply on the history
+  /// - `cursor`: the id of the last entry of the previous page, if any
+  /// - `limit`: the maximum number of entries to return
+  ///
+  pub async fn history_of(
+    &self,
+    user_id: &Uuid,
+    filter: &HistoryFilter,
+    cursor: Option<Uuid>,
+    limit: u32,
+  ) -> Result<Vec<HistoryEntry>, sqlx::Error> {
+    let rows = sqlx::query_as!(
+      HistoryRow,
+      r#"
+        with entries as (
+          select
+            tx.id,
+            case
+              when tx.is_state and tx.merges is not null then
+                case
+                  when used.sender = $1 and used.receiver_sys like 'tile:%' then 'refund'
+                  when used.sender = $1 then 'rescind'
+                  else 'accept'
+                end
+              when tx.is_state then 'initial'
+              when tx.sender = $1 then
+                case
+                  when not used.is_state then 'reject'
+                  when tx.receiver_sys like 'tile:%' then 'bid'
+                  else 'offer'
+                end
+              when tx.sender_sys = 'mint' then 'injection'
+              when tx.sender_sys like 'tile:%' then 'refund'
+              when not used.is_state and used.sender = $1 then 'refund'
+              else 'offer'
+            end as kind,
+            case when tx.is_state or tx.receiver = $1 then 'in' else 'out' end as direction,
+            tx.consumed_value as amount,
+            case
+              when tx.is_state and tx.merges is not null then
+                case when used.sender = $1 then used.receiver else used.sender end
+              when tx.sender = $1 then tx.receiver
+              else tx.sender
+            end as counterparty,
+            case
+              when tx.is_state and tx.merges is not null then
+                case when used.sender = $1 then used.receiver_sys else used.sender_sys end
+              when tx.sender = $1 then tx.receiver_sys
+              else tx.sender_sys
+            end as counterparty_sys,
+            (
+              select state.consumed_value + state.merged_value
+              from transactions state
+              where state.receiver = $1 and state.is_state and state.created_at <= tx.created_at
+              order by state.created_at desc
+              limit 1
+            ) as balance,
+            tx.note,
+            tx.created_at
+          from transactions tx
+          left join transactions used on used.id = tx.consumes
+          where (tx.sender = $1 or tx.receiver = $1)
+            and not (tx.is_state and tx.merges is null and tx.consumes is not null)
+        )
+        select
+          id as "id!",
+          kind as "kind!",
+          direction as "direction!",
+          amount as "amount!",
+          balance,
+          counterparty,
+          counterparty_sys,
+          note,
+          created_at as "created_at!"
+        from entries
+        where ($2::timestamptz is null or created_at >= $2)
+          and ($3::timestamptz is null or created_at < $3)
+          and ($4::uuid is null or counterparty = $4)
+          and ($5::text is null or counterparty_sys = $5)
+          and ($6::text is null or kind = $6)
+          and (
+            $7::uuid is null or
+            (created_at, id) < (select created_at, id from transactions where id = $7)
+          )
+        order by created_at desc, id desc
+        limit $8
+      "#,
+      user_id,
+      filter.since,
+      filter.until,
+      filter.counterparty,
+      filter.counterparty_sys,
+      filter.kind.map(EntryKind::as_str),
+      cursor,
+      i64::from(limit),
+    )
+    .fetch_all(&self.backend.pool)
+    .await?;
+
+    Ok(rows.into_iter().map(HistoryEntry::from).collect())
+  }
+}
+
+///
+/// Escapes a CSV field, also neutralising fields that spreadsheet
+/// software would otherwise interpret as formulas.
+///
+fn csv_field(value: &str) -> String {
+  let value = if value.starts_with(['=', '+', '-', '@']) {
+    format!("'{value}")
+  } else {
+    value.to_string()
+  };
+
+  if value.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value
+  }
+}
+
+///
+/// Renders given history entries as CSV, with a header row.
+///
+pub fn to_csv(entries: &[HistoryEntry]) -> String {
+  let mut csv = String::from("id,created_at,kind,direction,amount,balance,counterparty,note\n");
+
+  for entry in entries {
+    let row = [
+      entry.id.to_string(),
+      entry.created_at.to_rfc3339(),
+      entry.kind.as_str().to_string(),
+      entry.direction.as_str().to_string(),
+      entry.amount.to_string(),
+      entry.balance.map(|b| b.to_string()).unwrap_or_default(),
+      entry
+        .counterparty
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_default(),
+      entry.note.clone().unwrap_or_default(),
+    ];
+
+    csv.push_str(
+      &row
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(","),
+    );
+    csv.push('\n');
+  }
+
+  csv
+}
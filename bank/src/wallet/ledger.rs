@@ -1,123 +1,69 @@
-use sqlx::{postgres::Postgres, types::Uuid, FromRow, Pool, QueryBuilder};
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::Postgres, types::Uuid, Pool};
 
 use super::account::Account;
 use super::config::Config;
+use super::error::WalletError;
+use super::store::{LedgerStore, PostgresStore};
 use super::transaction::Transaction;
+use crate::auth::{user::VerificationStatus, AuthenticatedUser};
 
+///
+/// The OCSL ledger, on top of some storage backend (see `LedgerStore`). OCSL operations work
+/// with any backend, while reports and other queries (history, verification, etc.) are
+/// only available on the Postgres backend, which is the default.
+///
 #[derive(Debug, Clone)]
-pub struct Ledger {
-  pool: Pool<Postgres>,
+pub struct Ledger<S = PostgresStore> {
+  pub(super) backend: S,
   pub config: Config,
 }
 
+///
+/// The id of the system user (see the `system_user` migration), issuing transactions
+/// of scheduled jobs that don't act on behalf of any particular user.
+///
+const SYSTEM_USER: Uuid = Uuid::nil();
+
 impl Ledger {
   pub fn new(config: Config, pool: Pool<Postgres>) -> Self {
-    Self { pool, config }
+    Self::with_backend(config, PostgresStore::new(pool))
   }
 
-  pub async fn get_transaction(&self, id: &Uuid) -> Result<Transaction, sqlx::Error> {
-    match sqlx::query_as!(
-      Transaction,
-      "
-        select * from transactions where id = $1
-      ",
-      id
+  ///
+  /// Returns given user as the issuer of transactions that are made on their
+  /// behalf by scheduled jobs (e.g. expiring offers), rather than by some user
+  /// request. The returned user carries no token.
+  ///
+  pub(super) async fn on_behalf_of(
+    &self,
+    user_id: &Uuid,
+  ) -> Result<AuthenticatedUser, sqlx::Error> {
+    let user = sqlx::query!(
+      "select id, email, first_name, last_name, email_verified_at from users where id = $1",
+      user_id,
     )
-    .fetch_one(&self.pool)
-    .await
-    {
-      Ok(tx) => Ok(tx),
-      Err(e) => Err(e),
-    }
-  }
-
-  pub async fn find_balance(&self, account: &Account) -> Result<Transaction, sqlx::Error> {
-    let result = match account {
-      Account::User(user_id) => {
-        sqlx::query_as!(
-          Transaction,
-          "
-            select * from transactions
-            where receiver = $1
-              and is_state = true
-              and consumed is false and merged is false
-          ",
-          user_id
-        )
-        .fetch_one(&self.pool)
-        .await
-      }
-      Account::System(sys_id) => {
-        sqlx::query_as!(
-          Transaction,
-          "
-            select * from transactions
-            where receiver_sys = $1
-              and is_state = true
-              and consumed is false and merged is false
-          ",
-          sys_id
-        )
-        .fetch_one(&self.pool)
-        .await
-      }
-      Account::Invalid => return Err(sqlx::Error::RowNotFound),
-    };
+    .fetch_one(&self.backend.pool)
+    .await?;
 
-    match result {
-      Ok(tx) => Ok(tx),
-      Err(e) => Err(e),
-    }
+    Ok(AuthenticatedUser {
+      id: user.id,
+      email: user.email,
+      first_name: user.first_name,
+      last_name: user.last_name,
+      verification: VerificationStatus {
+        email_verified_at: user.email_verified_at,
+      },
+      token: String::new(),
+    })
   }
 
-  pub async fn find_open_offers(
-    &self,
-    account: &Account,
-    offset: u32,
-    limit: u32,
-  ) -> Result<Vec<Transaction>, sqlx::Error> {
-    let result = match account {
-      Account::User(user_id) => {
-        sqlx::query_as!(
-          Transaction,
-          "
-            select * from transactions
-            where receiver = $1
-              and consumed is false and merged is false
-            offset $2
-            limit $3
-          ",
-          user_id,
-          i64::from(offset),
-          i64::from(limit),
-        )
-        .fetch_all(&self.pool)
-        .await
-      }
-      Account::System(sys_id) => {
-        sqlx::query_as!(
-          Transaction,
-          "
-            select * from transactions
-            where receiver_sys = $1
-              and consumed is false and merged is false
-            offset $2
-            limit $3
-          ",
-          sys_id,
-          i64::from(offset),
-          i64::from(limit),
-        )
-        .fetch_all(&self.pool)
-        .await
-      }
-      Account::Invalid => return Err(sqlx::Error::RowNotFound),
-    };
-
-    match result {
-      Ok(txs) => Ok(txs),
-      Err(e) => Err(e),
-    }
+  ///
+  /// Returns the system user as the issuer of transactions made by scheduled
+  /// jobs on nobody's behalf (e.g. paying allowances).
+  ///
+  pub(super) async fn as_system(&self) -> Result<AuthenticatedUser, sqlx::Error> {
+    self.on_behalf_of(&SYSTEM_USER).await
   }
 
   pub async fn transaction_history(
@@ -139,51 +85,68 @@ impl Ledger {
       i64::from(offset),
       i64::from(limit),
     )
-    .fetch_all(&self.pool)
+    .fetch_all(&self.backend.pool)
     .await
     {
       Ok(txs) => Ok(txs),
       Err(e) => Err(e),
     }
   }
+}
+
+impl<S: LedgerStore> Ledger<S> {
+  pub fn with_backend(config: Config, backend: S) -> Self {
+    Self { backend, config }
+  }
+
+  pub async fn get_transaction(&self, id: &Uuid) -> Result<Transaction, sqlx::Error> {
+    self.backend.get(id).await
+  }
+
+  pub async fn find_balance(&self, account: &Account) -> Result<Transaction, sqlx::Error> {
+    self.backend.find_balance(account).await
+  }
+
+  pub async fn find_open_offers(
+    &self,
+    account: &Account,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Transaction>, sqlx::Error> {
+    self.backend.find_open_offers(account, offset, limit).await
+  }
 
+  ///
+  /// Stores given transactions in the ledger, all at once. Transactions consumed or merged
+  /// by the new ones are guaranteed not to be used twice, e.g. by concurrent operations
+  /// double spending a balance. Prefer using `commit_tx!` instead of calling this directly.
+  ///
+  /// ### Returns:
+  /// the stored transactions, or `WalletError::AlreadyUsedTransaction` if any of the
+  /// consumed or merged transactions is already used (or doesn't exist).
+  ///
   pub async fn store<const N: usize>(
     &self,
     txs: [Transaction; N],
-  ) -> Result<[Transaction; N], sqlx::Error> {
-    let mut query = QueryBuilder::new(
-      "insert into transactions (
-        sender, sender_sys, receiver, receiver_sys,
-        consumes, consumed_value, merges, merged_value,
-        note, issued_by
-      )",
-    );
-
-    query.push_values(&txs, |mut b, tx| {
-      b.push_bind(tx.sender);
-      b.push_bind(tx.sender_sys.clone());
-      b.push_bind(tx.receiver);
-      b.push_bind(tx.receiver_sys.clone());
-      b.push_bind(tx.consumes);
-      b.push_bind(tx.consumed_value);
-      b.push_bind(tx.merges);
-      b.push_bind(tx.merged_value);
-      b.push_bind(tx.note.clone());
-      b.push_bind(tx.issued_by);
-    });
-
-    query.push("returning *");
+  ) -> Result<[Transaction; N], WalletError> {
+    self.store_expiring(txs, None).await
+  }
 
-    match query.build().fetch_all(&self.pool).await {
-      Ok(rows) => Ok(
-        rows
-          .iter()
-          .map(|row| Transaction::from_row(row).unwrap())
-          .collect::<Vec<Transaction>>()
-          .try_into()
-          .unwrap(),
-      ),
-      Err(e) => Err(e),
-    }
+  ///
+  /// Stores given transactions in the ledger, like `store()`, setting the first of them
+  /// (an offer) to expire at given time, if any. The offer is never stored without its
+  /// expiry. Prefer using `commit_tx!` instead of calling this directly.
+  ///
+  pub async fn store_expiring<const N: usize>(
+    &self,
+    txs: [Transaction; N],
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<[Transaction; N], WalletError> {
+    self
+      .backend
+      .store(txs.into(), expires_at)
+      .await?
+      .try_into()
+      .map_err(|_| WalletError::Unknown)
   }
 }
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Duration, Utc};
+use log::error;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use super::error::WalletError;
+use super::ledger::Ledger;
+use super::store::LedgerStore;
+
+///
+/// Configuration for limits on how much users can spend from their balance, i.e. offer
+/// to other accounts (including bids and tips), per (UTC) day and per offer. Zero means
+/// unlimited. Admins can override these limits per user, and users can lower them further
+/// for themselves.
+///
+/// ### Example (TOML):
+/// ```toml
+/// daily = 64
+/// per_transaction = 32
+/// ```
+///
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SpendingLimits {
+  pub daily: u32,
+  pub per_transaction: u32,
+}
+
+///
+/// Spending limits set for a specific user, by an admin or by the user themselves.
+/// `None` means the limit isn't set, and zero means unlimited.
+///
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+  pub daily: Option<u32>,
+  pub per_transaction: Option<u32>,
+}
+
+///
+/// Limits set for a user: limits set by admins replace the configured ones,
+/// while limits set by the user can only lower them.
+///
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LimitOverrides {
+  pub admin: Limits,
+  pub user: Limits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitSetter {
+  Admin,
+  User,
+}
+
+///
+/// Spending limits in effect for a user (`None` meaning unlimited), alongside how much
+/// they have spent today, when that resets, and the limits set for them specifically.
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct SpendingStatus {
+  pub daily: Option<u32>,
+  pub per_transaction: Option<u32>,
+  pub spent: i64,
+  pub resets_at: DateTime<Utc>,
+  pub overrides: LimitOverrides,
+}
+
+///
+/// Returns the start of the (UTC) day given time falls into, and the start of the next day.
+///
+pub fn day_of(time: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+  let start = time
+    .date_naive()
+    .and_hms_opt(0, 0, 0)
+    .map_or(time, |start| start.and_utc());
+
+  (start, start + Duration::days(1))
+}
+
+fn effective(configured: u32, admin: Option<u32>, user: Option<u32>) -> Option<u32> {
+  let limit = Some(admin.unwrap_or(configured)).filter(|limit| *limit > 0);
+
+  match (limit, user.filter(|limit| *limit > 0)) {
+    (Some(limit), Some(user)) => Some(limit.min(user)),
+    (limit, user) => limit.or(user),
+  }
+}
+
+impl<S: LedgerStore> Ledger<S> {
+  ///
+  /// Returns the spending limits in effect for given user, and how much they have spent today.
+  /// Only offers made from the user's balance count as spending, so rejecting an offer
+  /// (or returning the rest of a partially accepted one) doesn't.
+  ///
+  /// ### Params:
+  /// - `user_id`: the user to check
+  /// - `excluding`: an offer to leave out of today's spending, e.g. when re-checking it
+  ///
+  pub async fn spending_status(
+    &self,
+    user_id: &Uuid,
+    excluding: Option<&Uuid>,
+  ) -> Result<SpendingStatus, sqlx::Error> {
+    let (today, resets_at) = day_of(Utc::now());
+    let overrides = self.backend.limit_overrides(user_id).await?;
+    let spent = self.backend.spent_since(user_id, today, excluding).await?;
+    let config = &self.config.spending;
+
+    Ok(SpendingStatus {
+      daily: effective(config.daily, overrides.admin.daily, overrides.user.daily),
+      per_transaction: effective(
+        config.per_transaction,
+        overrides.admin.per_transaction,
+        overrides.user.per_transaction,
+      ),
+      spent,
+      resets_at,
+      overrides,
+    })
+  }
+
+  ///
+  /// Checks whether given user can spend given amount (see `Ledger::spending_status()`).
+  ///
+  /// ### Returns:
+  /// the spending status of the user (before spending the amount), or `WalletError::LimitExceeded`
+  /// if spending the amount would exceed any of the user's limits, with when the limit resets
+  /// (or `None`, if the amount exceeds the per-transaction limit).
+  ///
+  pub async fn check_spending(
+    &self,
+    user_id: &Uuid,
+    amount: u32,
+    excluding: Option<&Uuid>,
+  ) -> Result<SpendingStatus, WalletError> {
+    let status = self
+      .spending_status(user_id, excluding)
+      .await
+      .map_err(|err| {
+        error!("Failed to check spending of {user_id}: {err:?}");
+        WalletError::Unknown
+      })?;
+
+    if status.per_transaction.is_some_and(|limit| amount > limit) {
+      return Err(WalletError::LimitExceeded { resets_at: None });
+    }
+
+    if status
+      .daily
+      .is_some_and(|limit| status.spent + i64::from(amount) > i64::from(limit))
+    {
+      return Err(WalletError::LimitExceeded {
+        resets_at: Some(status.resets_at),
+      });
+    }
+
+    Ok(status)
+  }
+
+  ///
+  /// Sets spending limits of given user, replacing limits previously set by the same setter.
+  ///
+  /// ### Returns:
+  /// the spending status of the user with the new limits.
+  ///
+  pub async fn set_spending_limits(
+    &self,
+    user_id: &Uuid,
+    setter: LimitSetter,
+    limits: Limits,
+  ) -> Result<SpendingStatus, WalletError> {
+    if [limits.daily, limits.per_transaction]
+      .into_iter()
+      .flatten()
+      .any(|limit| i32::try_from(limit).is_err())
+    {
+      return Err(WalletError::ErroneousTransaction);
+    }
+
+    self
+      .backend
+      .set_limits(user_id, setter, limits)
+      .await
+      .map_err(|err| {
+        error!("Failed to set spending limits of {user_id}: {err:?}");
+        WalletError::Unknown
+      })?;
+
+    self.spending_status(user_id, None).await.map_err(|err| {
+      error!("Failed to check spending of {user_id}: {err:?}");
+      WalletError::Unknown
+    })
+  }
+}
@@ -18,6 +18,12 @@
 ///   Err(_) => { ... },
 /// }
 /// ```
+/// The first transaction (an offer) can also be set to expire, along with storing it:
+/// ```rs
+/// let [offer, rest] = commit_tx!(offer, rest; to ledger, expiring at)?;
+/// ```
+/// where `at` is an `Option<DateTime<Utc>>`.
+///
 /// ### Returns:
 /// `Result<[Transaction; N], sqlx::Error>`, when given `N` transactions.
 ///
@@ -25,6 +31,9 @@
 macro_rules! commit_tx {
   [$($tx:expr),*; to $ledger:expr] => {
     $ledger.store([$($tx),*]).await
+  };
+  [$($tx:expr),*; to $ledger:expr, expiring $at:expr] => {
+    $ledger.store_expiring([$($tx),*], $at).await
   }
 }
 
@@ -6,19 +6,32 @@ use axum::{
 use tower_http::cors::{Any, CorsLayer};
 
 mod account;
+pub mod allowance;
 mod api;
 pub mod auth;
 pub mod config;
+pub mod economy;
 pub mod error;
+mod expiry;
+mod graph;
+mod history;
 mod ledger;
+pub mod limits;
 mod macros;
 pub mod operations;
+mod recipient;
+pub mod store;
+#[cfg(test)]
+mod tests;
 mod transaction;
+mod verify;
 
 pub use account::Account;
 pub use ledger::Ledger;
 pub use transaction::Transaction;
 
+use recipient::RecipientLookups;
+
 pub fn router(ledger: &Ledger) -> Router {
   let cors = CorsLayer::new()
     .allow_methods(Any)
@@ -26,20 +39,37 @@ pub fn router(ledger: &Ledger) -> Router {
     .allow_origin(Any);
 
   let ledger = ledger.clone();
+  let lookups = RecipientLookups::default();
 
   Router::new()
     .route("/balance", get(api::balance))
     .route("/offers", get(api::offers))
     .route("/history", get(api::history))
+    .route("/history/entries", get(api::history_entries))
+    .route("/history/export", get(api::export_history))
     .route("/accept", post(api::accept))
     .route("/reject", post(api::reject))
     .route("/rescind", post(api::rescind))
     .route("/offer", post(api::offer))
+    .route("/send", post(api::send))
+    .route("/limits", get(api::limits).put(api::set_limits))
     // --- ADMIN APIS --- \\
     .route("/admin/balance/{id}", get(api::user_balance))
+    .route(
+      "/admin/limits/{id}",
+      get(api::user_limits).put(api::set_user_limits),
+    )
     .route("/admin/inject", post(api::inject))
     .route("/admin/partially-accept", post(api::partially_accept))
+    .route("/admin/verify", get(api::verify))
+    .route("/admin/mint", get(api::mint))
+    .route("/admin/economy", get(api::economy))
+    .route("/admin/economy/series", get(api::economy_series))
+    .route("/admin/accounts", get(api::system_accounts))
+    .route("/admin/accounts/{name}", get(api::system_balance))
+    .route("/admin/graph", get(api::graph))
     // --- LAYERS --- \\
     .layer(Extension(ledger))
+    .layer(Extension(lookups))
     .layer(cors)
 }
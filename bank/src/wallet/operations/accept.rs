@@ -1,10 +1,11 @@
 use super::super::super::auth::AuthenticatedUser;
 use super::super::error::WalletError;
 use super::super::ledger::Ledger;
+use super::super::store::LedgerStore;
 use super::super::transaction::Transaction;
 use crate::{commit_tx, tx};
 
-impl Ledger {
+impl<S: LedgerStore> Ledger<S> {
   ///
   /// Accepts a given offer, merging it into the receiver's prior state,
   /// hence updating their balance.
@@ -42,7 +43,7 @@ impl Ledger {
           to self
         ] {
           Ok([merged]) => Ok(merged),
-          Err(_) => Err(WalletError::Unknown),
+          Err(err) => Err(err),
         }
       }
       Err(err) => Err(err),
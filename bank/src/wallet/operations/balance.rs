@@ -1,11 +1,12 @@
 use super::super::account::Account;
 use super::super::error::WalletError;
 use super::super::ledger::Ledger;
+use super::super::store::LedgerStore;
 use super::super::transaction::Transaction;
 use crate::auth::AuthenticatedUser;
 use crate::{commit_tx, tx};
 
-impl Ledger {
+impl<S: LedgerStore> Ledger<S> {
   ///
   /// Returns the balance transaction of the given account. If the account
   /// does not have any prior state, will instead initialize their account
@@ -19,7 +20,8 @@ impl Ledger {
   ///             account, if it didn't already exist.
   ///
   /// ### Returns:
-  /// The balance transaction, or appropriate error.
+  /// The balance transaction, or appropriate error. If the account is concurrently
+  /// initialized elsewhere, the balance resulting from that initialization is returned.
   ///
   pub async fn balance_or_init(
     &self,
@@ -35,7 +37,9 @@ impl Ledger {
           to self
         ] {
           Ok([balance]) => Ok(balance),
-          Err(_) => Err(WalletError::Unknown),
+          // only one live state is allowed per account, so failing
+          // here might mean the account was initialized meanwhile.
+          Err(err) => self.find_balance(account).await.map_err(|_| err),
         }
       }
     }
@@ -1,11 +1,11 @@
 use log::error;
 use serde::Serialize;
-use sqlx::types::Uuid;
 
 use super::super::super::auth::AuthenticatedUser;
-use super::super::account::Account;
+use super::super::account::{Account, MINT};
 use super::super::error::WalletError;
 use super::super::ledger::Ledger;
+use super::super::store::LedgerStore;
 use super::super::transaction::Transaction;
 use crate::{commit_tx, tx};
 
@@ -13,31 +13,31 @@ use crate::{commit_tx, tx};
 #[serde(tag = "type")]
 pub enum InjectResult {
   ToUser {
-    init: Transaction,
+    minted: Transaction,
     offer: Transaction,
   },
   ToSysUser {
-    init: Transaction,
+    minted: Transaction,
     offer: Transaction,
     merged: Transaction,
   },
 }
 
-impl Ledger {
+impl<S: LedgerStore> Ledger<S> {
   ///
-  /// Injects given amount of tokens into the receiver's account. The process is done by initializing
-  /// a temporary account with the given amount, and then having said temporary account offer the amount
-  /// to the receiver. If the receiver is a system account, the offer will be automatically accepted and merged.
+  /// Injects given amount of tokens into the receiver's account. The process is done by minting
+  /// the amount into the mint account, and then having the mint offer the amount to the receiver.
+  /// If the receiver is a system account, the offer will be automatically accepted and merged.
   ///
   /// ```
-  /// ──▷ :tmp ──▷ tmp:a
+  /// ──▷ :mint ══▷ :mint ──▷ mint:a
   /// ```
   /// or
   /// ```
-  /// ──▷ :tmp ──▷ tmp:sys ──┐
-  ///                        │
-  ///                        ▽
-  /// ──▷ sys:sys ══════▷ sys:sys
+  /// ──▷ :mint ══▷ :mint ──▷ mint:sys ──┐
+  ///                                    │
+  ///                                    ▽
+  /// ──▷ sys:sys ══════════════════▷ sys:sys
   /// ```
   ///
   /// ### Params:
@@ -47,9 +47,9 @@ impl Ledger {
   /// - `issued_by`: the uuid of the user who is injecting the tokens
   ///
   /// ### Returns:
-  /// `InjectResult { init, offer, merge }`, where:
-  /// - `init` is the initial state of the temporary account created for injection,
-  /// - `offer` is an offer from the temporary account to the receiver,
+  /// `InjectResult { minted, offer, merge }`, where:
+  /// - `minted` is the state of the mint account after minting the amount,
+  /// - `offer` is an offer from the mint account to the receiver,
   /// - `merge`, optional, is the new state of the receiver if it is a system account, the offer will be
   ///    automatically accepted and merged in that scenario.
   ///
@@ -60,22 +60,19 @@ impl Ledger {
     note: Option<String>,
     issuer: &AuthenticatedUser,
   ) -> Result<InjectResult, WalletError> {
-    let tmp_account = Account::of_sys_user(format!("tmp-{}", Uuid::new_v4()).as_str());
-
-    let [init] = commit_tx! [
-      tx! { => &tmp_account; using amount; by issuer };
-      to self
-    ]
-    .map_err(|_| WalletError::Unknown)?;
+    match receiver {
+      Account::Invalid => return Err(WalletError::UnauthorizedTransaction),
+      Account::System(sys) if sys == MINT => return Err(WalletError::UnauthorizedTransaction),
+      _ => {}
+    }
 
-    let [offer] = commit_tx! [
-      tx! { &tmp_account => receiver; using &init, amount; by issuer, note };
-      to self
-    ]
-    .map_err(|_| WalletError::Unknown)?;
+    let minted = self.mint(amount, note.clone(), issuer).await?;
+    let offer = self
+      .offer_from_balance(&Account::mint(), receiver, amount, note, None, issuer)
+      .await?
+      .offer;
 
     match receiver {
-      Account::User(_) => Ok(InjectResult::ToUser { init, offer }),
       Account::System(_) => match self.balance_or_init(receiver, None, issuer).await {
         Ok(balance) => {
           match commit_tx! [
@@ -83,19 +80,19 @@ impl Ledger {
             to self
           ] {
             Ok([merged]) => Ok(InjectResult::ToSysUser {
-              init,
+              minted,
               offer,
               merged,
             }),
             Err(err) => {
               error!("Failed to merge offer: {err}");
-              Err(WalletError::Unknown)
+              Err(err)
             }
           }
         }
         Err(err) => Err(err),
       },
-      Account::Invalid => Err(WalletError::UnauthorizedTransaction),
+      _ => Ok(InjectResult::ToUser { minted, offer }),
     }
   }
 }
@@ -0,0 +1,103 @@
+use serde::Serialize;
+
+use super::super::super::auth::AuthenticatedUser;
+use super::super::account::{Account, MINT};
+use super::super::error::WalletError;
+use super::super::ledger::Ledger;
+use super::super::store::LedgerStore;
+use super::super::transaction::Transaction;
+use crate::commit_tx;
+
+///
+/// Totals of the mint account: `minted` is the amount of coins minted so far,
+/// `reserve` is the amount of minted coins still held by the mint, and
+/// `circulating` is the amount of minted coins that left the mint.
+///
+#[derive(Serialize, Debug)]
+pub struct MintSummary {
+  pub minted: i64,
+  pub reserve: i64,
+  pub circulating: i64,
+}
+
+impl<S: LedgerStore> Ledger<S> {
+  ///
+  /// Mints given amount of new coins into the mint account. The minted coins form
+  /// a new state for the mint account, which also merges its prior state (if any).
+  /// Minted coins can then be offered to other accounts from the mint's balance.
+  ///
+  /// ```
+  /// ──▷ :mint ══▷ :mint
+  /// ```
+  ///
+  /// ### Params:
+  /// - `amount`: the amount to mint
+  /// - `note`: an optional note to attach to the new state
+  /// - `issuer`: the user who is minting the coins
+  ///
+  /// ### Returns:
+  /// the new state of the mint account.
+  ///
+  pub async fn mint(
+    &self,
+    amount: u32,
+    note: Option<String>,
+    issuer: &AuthenticatedUser,
+  ) -> Result<Transaction, WalletError> {
+    let amount = i32::try_from(amount).map_err(|_| WalletError::ErroneousTransaction)?;
+    if amount == 0 {
+      return Err(WalletError::ErroneousTransaction);
+    }
+
+    let prior = match self.find_balance(&Account::mint()).await {
+      Ok(balance) => Some(balance),
+      Err(sqlx::Error::RowNotFound) => None,
+      Err(_) => return Err(WalletError::Unknown),
+    };
+
+    let minted = Transaction {
+      receiver_sys: Some(MINT.to_string()),
+      consumed_value: amount,
+      merges: prior.as_ref().and_then(|prior| prior.id),
+      merged_value: prior.map_or(0, |prior| i32::try_from(prior.total()).unwrap_or_default()),
+      note,
+      issued_by: issuer.id,
+      ..Default::default()
+    };
+
+    match commit_tx![minted; to self] {
+      Ok([minted]) => Ok(minted),
+      Err(err) => Err(err),
+    }
+  }
+}
+
+impl Ledger {
+  ///
+  /// Returns the totals of the mint account (see `MintSummary`). Coins are minted by
+  /// states of the mint account without a sender, each issuing its `consumed_value`.
+  ///
+  pub async fn mint_summary(&self) -> Result<MintSummary, sqlx::Error> {
+    let minted = sqlx::query_scalar!(
+      r#"
+        select coalesce(sum(consumed_value), 0)::bigint as "minted!" from transactions
+        where receiver_sys = $1 and sender is null and sender_sys is null
+      "#,
+      MINT,
+    )
+    .fetch_one(&self.backend.pool)
+    .await?;
+
+    let reserve = match self.find_balance(&Account::mint()).await {
+      Ok(balance) => i64::from(balance.total()),
+      Err(sqlx::Error::RowNotFound) => 0,
+      Err(err) => return Err(err),
+    };
+
+    Ok(MintSummary {
+      minted,
+      reserve,
+      circulating: minted - reserve,
+    })
+  }
+}
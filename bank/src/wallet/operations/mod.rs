@@ -6,6 +6,7 @@ pub mod accept;
 ///
 pub mod balance;
 pub mod inject;
+pub mod mint;
 pub mod offer;
 pub mod partially_accept;
 pub mod reject;
@@ -1,9 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 
 use super::super::super::auth::AuthenticatedUser;
 use super::super::account::Account;
 use super::super::error::WalletError;
 use super::super::ledger::Ledger;
+use super::super::store::LedgerStore;
 use super::super::transaction::Transaction;
 use crate::{commit_tx, tx};
 
@@ -11,9 +13,10 @@ use crate::{commit_tx, tx};
 pub struct OfferResult {
   pub offer: Transaction,
   pub rest: Transaction,
+  pub expires_at: Option<DateTime<Utc>>,
 }
 
-impl Ledger {
+impl<S: LedgerStore> Ledger<S> {
   ///
   /// Offers the given amount from a sender account to a receiver account. Will also
   /// create a new state for the sender, based on the remainder of their balance.
@@ -28,12 +31,19 @@ impl Ledger {
   /// - `receiver`: the receiver account
   /// - `amount`: the amount to offer
   /// - `note`: an optional note to attach to the offer
+  /// - `expires_at`: an optional time after which the offer is rejected back to the sender,
+  ///   if not accepted (or rejected) by then. Only offers to users can expire.
   /// - `issuer`: the user who is requesting the offer
   ///
   /// ### Returns:
-  /// `OfferResult { offered, rest }`, where:
+  /// `OfferResult { offered, rest, expires_at }`, where:
   /// - `offered` is an offer of the requested amount,
-  /// - `rest` is the new state of the sender.
+  /// - `rest` is the new state of the sender,
+  /// - `expires_at` is when the offer expires, if it does. This is `None` if the expiry
+  ///   couldn't be set, in which case the offer stays open until accepted or rejected.
+  ///
+  /// Fails with `WalletError::LimitExceeded` if the offer exceeds the sender's spending
+  /// limits (see `Ledger::check_spending()`).
   ///
   pub async fn offer_from_balance(
     &self,
@@ -41,12 +51,21 @@ impl Ledger {
     receiver: &Account,
     amount: u32,
     note: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
     issuer: &AuthenticatedUser,
   ) -> Result<OfferResult, WalletError> {
     if amount == 0 {
       return Err(WalletError::ErroneousTransaction);
     }
 
+    if expires_at.is_some_and(|at| at <= Utc::now() || !matches!(receiver, Account::User(_))) {
+      return Err(WalletError::ErroneousTransaction);
+    }
+
+    if let Account::User(user_id) = sender {
+      self.check_spending(user_id, amount, None).await?;
+    }
+
     match self.balance_or_init(sender, None, issuer).await {
       Ok(balance) => {
         let total = balance.total();
@@ -57,13 +76,47 @@ impl Ledger {
         match commit_tx! [
           tx! { sender => receiver; using &balance, amount; by issuer, note },
           tx! { sender => sender; using &balance, total - amount; by issuer };
-          to self
+          to self, expiring expires_at
         ] {
-          Ok([offer, rest]) => Ok(OfferResult { offer, rest }),
-          Err(_) => Err(WalletError::Unknown),
+          Ok([offer, rest]) => Ok(OfferResult {
+            offer,
+            rest,
+            expires_at,
+          }),
+          Err(err) => Err(err),
         }
       }
       Err(err) => Err(err),
     }
   }
+
+  ///
+  /// Checks whether given amount can be offered from the balance of given account, without
+  /// making the offer: the amount must be within the sender's spending limits and balance.
+  /// Handlers use this to check offers before asking for (or using up) a step-up confirmation.
+  ///
+  /// ### Returns:
+  /// whether the offer needs a step-up confirmation (see `Config::requires_step_up()`).
+  ///
+  pub async fn check_offer_from_balance(
+    &self,
+    sender: &Account,
+    amount: u32,
+    issuer: &AuthenticatedUser,
+  ) -> Result<bool, WalletError> {
+    if amount == 0 {
+      return Err(WalletError::ErroneousTransaction);
+    }
+
+    let spent = match sender {
+      Account::User(user_id) => self.check_spending(user_id, amount, None).await?.spent,
+      _ => 0,
+    };
+
+    if self.balance_or_init(sender, None, issuer).await?.total() < amount {
+      return Err(WalletError::InsufficientFunds);
+    }
+
+    Ok(self.config.requires_step_up(spent, amount))
+  }
 }
@@ -4,6 +4,7 @@ use super::super::super::auth::AuthenticatedUser;
 use super::super::account::Account;
 use super::super::error::WalletError;
 use super::super::ledger::Ledger;
+use super::super::store::LedgerStore;
 use super::super::transaction::Transaction;
 use crate::{commit_tx, tx};
 
@@ -13,7 +14,7 @@ pub struct PartialAcceptResult {
   pub merged: Transaction,
 }
 
-impl Ledger {
+impl<S: LedgerStore> Ledger<S> {
   ///
   /// Partially accepts an offer, merging given amount of it
   /// into receiver's prior state, and offering the remainder
@@ -61,7 +62,7 @@ impl Ledger {
           to self
         ] {
           Ok([returned, merged]) => Ok(PartialAcceptResult { returned, merged }),
-          Err(_) => Err(WalletError::Unknown),
+          Err(err) => Err(err),
         }
       }
       Err(err) => Err(err),
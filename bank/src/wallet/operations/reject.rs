@@ -2,10 +2,11 @@ use super::super::super::auth::AuthenticatedUser;
 use super::super::account::Account;
 use super::super::error::WalletError;
 use super::super::ledger::Ledger;
+use super::super::store::LedgerStore;
 use super::super::transaction::Transaction;
 use crate::{commit_tx, tx};
 
-impl Ledger {
+impl<S: LedgerStore> Ledger<S> {
   ///
   /// Rejects an offer, offering the amount back to the original sender.
   ///
@@ -38,7 +39,7 @@ impl Ledger {
       to self
     ] {
       Ok([revert]) => Ok(revert),
-      Err(_) => Err(WalletError::Unknown),
+      Err(err) => Err(err),
     }
   }
 }
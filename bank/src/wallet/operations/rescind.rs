@@ -1,10 +1,11 @@
 use super::super::super::auth::AuthenticatedUser;
 use super::super::error::WalletError;
 use super::super::ledger::Ledger;
+use super::super::store::LedgerStore;
 use super::super::transaction::Transaction;
 use crate::{commit_tx, tx};
 
-impl Ledger {
+impl<S: LedgerStore> Ledger<S> {
   ///
   /// Rescinds a given offer, merging it back into the sender's prior state,
   /// ```
@@ -40,7 +41,7 @@ impl Ledger {
           to self
         ] {
           Ok([merged]) => Ok(merged),
-          Err(_) => Err(WalletError::Unknown),
+          Err(err) => Err(err),
         }
       }
       Err(err) => Err(err),
@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Uuid;
+
+use super::ledger::Ledger;
+
+///
+/// How many recipients a user can look up by email within `LOOKUP_WINDOW`.
+/// Sending to an email reveals whether it belongs to a user (and their id),
+/// so lookups are limited to keep users from being enumerated by email.
+///
+const MAX_EMAIL_LOOKUPS: usize = 20;
+const LOOKUP_WINDOW: Duration = Duration::from_hours(1);
+
+///
+/// Identifies a user receiving coins from another user,
+/// either by their id, their email or their public handle.
+///
+/// ```json
+/// { "id": "6f1c..." }
+/// { "email": "someone@example.com" }
+/// { "handle": "someone" }
+/// ```
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Recipient {
+  Id(Uuid),
+  Email(String),
+  Handle(String),
+}
+
+impl Recipient {
+  ///
+  /// Whether finding this recipient would reveal something about
+  /// them that the sender didn't already know (i.e. their id).
+  ///
+  fn is_private(&self) -> bool {
+    matches!(self, Recipient::Email(_))
+  }
+}
+
+///
+/// Tracks lookups of recipients by email, per user, within sliding windows.
+/// Lookups are tracked in memory (per instance), similar to reaction limits.
+///
+#[derive(Clone, Default)]
+pub struct RecipientLookups {
+  hits: Arc<DashMap<Uuid, VecDeque<Instant>>>,
+}
+
+impl RecipientLookups {
+  ///
+  /// Checks whether given user can look up given recipient, and if so,
+  /// records the lookup. Lookups by id or handle are not limited.
+  ///
+  pub fn allows(&self, user_id: &Uuid, recipient: &Recipient) -> bool {
+    if !recipient.is_private() {
+      return true;
+    }
+
+    self
+      .hits
+      .retain(|_, hits| hits.back().is_some_and(|at| at.elapsed() <= LOOKUP_WINDOW));
+
+    let mut hits = self.hits.entry(*user_id).or_default();
+    while hits.front().is_some_and(|at| at.elapsed() > LOOKUP_WINDOW) {
+      hits.pop_front();
+    }
+    if hits.len() >= MAX_EMAIL_LOOKUPS {
+      return false;
+    }
+
+    hits.push_back(Instant::now());
+    true
+  }
+}
+
+impl Ledger {
+  ///
+  /// Returns the id of given recipient, if such a user exists. Users are
+  /// only found by handle if they have a public profile.
+  ///
+  pub async fn find_recipient(&self, recipient: &Recipient) -> Result<Option<Uuid>, sqlx::Error> {
+    match recipient {
+      Recipient::Id(id) => {
+        sqlx::query_scalar!("select id from users where id = $1", id)
+          .fetch_optional(&self.backend.pool)
+          .await
+      }
+      Recipient::Email(email) => {
+        sqlx::query_scalar!("select id from users where email = $1", email)
+          .fetch_optional(&self.backend.pool)
+          .await
+      }
+      Recipient::Handle(handle) => {
+        sqlx::query_scalar!(
+          "select user_id from profiles where handle = lower($1) and public",
+          handle
+        )
+        .fetch_optional(&self.backend.pool)
+        .await
+      }
+    }
+  }
+
+  ///
+  /// Returns how much given user has offered to other users since given time,
+  /// i.e. the value of offers consuming their state. Offers that were later
+  /// rejected or rescinded are also included, while the transactions returning
+  /// them (e.g. the rejections themselves) are not.
+  ///
+  pub async fn sent_to_users_since(
+    &self,
+    user_id: &Uuid,
+    since: DateTime<Utc>,
+  ) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+      r#"
+        select coalesce(sum(tx.consumed_value), 0)::bigint as "sent!"
+        from transactions tx
+        join transactions used on used.id = tx.consumes
+        where tx.sender = $1
+          and tx.is_state is false
+          and used.is_state is true
+          and tx.receiver is not null
+          and tx.receiver <> $1
+          and tx.created_at > $2
+      "#,
+      user_id,
+      since,
+    )
+    .fetch_one(&self.backend.pool)
+    .await
+  }
+}
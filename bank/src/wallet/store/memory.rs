@@ -0,0 +1,255 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use chrono::{DateTime, Utc};
+use sqlx::types::Uuid;
+
+use super::super::account::Account;
+use super::super::error::WalletError;
+use super::super::limits::{LimitOverrides, LimitSetter, Limits};
+use super::super::transaction::Transaction;
+use super::LedgerStore;
+
+#[derive(Debug, Default)]
+struct Transactions {
+  all: Vec<Transaction>,
+  index: HashMap<Uuid, usize>,
+  // positions of live (unused) transactions, in
+  // the order they were stored.
+  live: BTreeSet<usize>,
+  expiries: HashMap<Uuid, DateTime<Utc>>,
+  limits: HashMap<Uuid, LimitOverrides>,
+}
+
+impl Transactions {
+  fn get(&self, id: &Uuid) -> Option<&Transaction> {
+    self.index.get(id).map(|i| &self.all[*i])
+  }
+
+  fn live(&self) -> impl Iterator<Item = &Transaction> {
+    self.live.iter().map(|i| &self.all[*i])
+  }
+}
+
+///
+/// An in-memory ledger store, which keeps transactions in the process, with the same
+/// guarantees as the database (see `LedgerStore`). Useful for exercising OCSL operations
+/// without a database, e.g. in tests or simulations. Clones share the same transactions.
+///
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+  transactions: Arc<Mutex<Transactions>>,
+}
+
+impl MemoryStore {
+  fn lock(&self) -> MutexGuard<'_, Transactions> {
+    self
+      .transactions
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+  }
+
+  ///
+  /// Returns all transactions stored so far, in the order they were stored.
+  ///
+  pub fn transactions(&self) -> Vec<Transaction> {
+    self.lock().all.clone()
+  }
+
+  ///
+  /// Returns when given offer expires, if it was set to.
+  ///
+  #[cfg(test)]
+  pub fn expiry_of(&self, offer: &Uuid) -> Option<DateTime<Utc>> {
+    self.lock().expiries.get(offer).copied()
+  }
+}
+
+///
+/// Checks the constraints the database enforces on each row of the transactions table.
+///
+fn is_well_formed(tx: &Transaction) -> bool {
+  let sender = (tx.sender.is_some(), tx.sender_sys.is_some());
+  let receiver = (tx.receiver.is_some(), tx.receiver_sys.is_some());
+
+  sender != (true, true)
+    && matches!(receiver, (true, false) | (false, true))
+    && (tx.is_state || tx.merges.is_none())
+    && (sender != (false, false)) == tx.consumes.is_some()
+}
+
+impl LedgerStore for MemoryStore {
+  async fn get(&self, id: &Uuid) -> Result<Transaction, sqlx::Error> {
+    self.lock().get(id).cloned().ok_or(sqlx::Error::RowNotFound)
+  }
+
+  async fn find_balance(&self, account: &Account) -> Result<Transaction, sqlx::Error> {
+    if *account == Account::Invalid {
+      return Err(sqlx::Error::RowNotFound);
+    }
+
+    self
+      .lock()
+      .live()
+      .find(|tx| tx.is_state && tx.receiver_account() == *account)
+      .cloned()
+      .ok_or(sqlx::Error::RowNotFound)
+  }
+
+  async fn find_open_offers(
+    &self,
+    account: &Account,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Transaction>, sqlx::Error> {
+    if *account == Account::Invalid {
+      return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(
+      self
+        .lock()
+        .live()
+        .filter(|tx| tx.receiver_account() == *account)
+        .skip(offset as usize)
+        .take(limit as usize)
+        .cloned()
+        .collect(),
+    )
+  }
+
+  async fn store(
+    &self,
+    txs: Vec<Transaction>,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<Vec<Transaction>, WalletError> {
+    let mut transactions = self.lock();
+
+    let used = txs
+      .iter()
+      .flat_map(|tx| [tx.consumes, tx.merges])
+      .flatten()
+      .collect::<HashSet<_>>();
+
+    if used
+      .iter()
+      .any(|id| transactions.get(id).is_none_or(Transaction::is_used))
+    {
+      return Err(WalletError::AlreadyUsedTransaction);
+    }
+
+    let created_at = Utc::now();
+    let stored = txs
+      .into_iter()
+      .map(|tx| Transaction {
+        id: Some(Uuid::new_v4()),
+        is_state: (tx.sender.is_none() && tx.sender_sys.is_none())
+          || (tx.sender.is_some() && tx.sender == tx.receiver)
+          || (tx.sender_sys.is_some() && tx.sender_sys == tx.receiver_sys),
+        consumed: false,
+        merged: false,
+        created_at,
+        ..tx
+      })
+      .collect::<Vec<_>>();
+
+    if !stored.iter().all(is_well_formed) {
+      return Err(WalletError::Unknown);
+    }
+
+    // each account can have only one live state, which
+    // (similar to the database) is checked after the states
+    // used by the new transactions are marked as such.
+    let mut states = transactions
+      .live()
+      .filter(|tx| tx.is_state && !tx.id.is_some_and(|id| used.contains(&id)))
+      .map(Transaction::receiver_account)
+      .collect::<HashSet<_>>();
+
+    if !stored
+      .iter()
+      .filter(|tx| tx.is_state)
+      .all(|tx| states.insert(tx.receiver_account()))
+    {
+      return Err(WalletError::Unknown);
+    }
+
+    for tx in &stored {
+      if let Some(i) = tx
+        .consumes
+        .and_then(|id| transactions.index.get(&id).copied())
+      {
+        transactions.all[i].consumed = true;
+        transactions.live.remove(&i);
+      }
+      if let Some(i) = tx
+        .merges
+        .and_then(|id| transactions.index.get(&id).copied())
+      {
+        transactions.all[i].merged = true;
+        transactions.live.remove(&i);
+      }
+    }
+
+    for tx in &stored {
+      let i = transactions.all.len();
+      transactions.index.extend(tx.id.map(|id| (id, i)));
+      transactions.live.insert(i);
+      transactions.all.push(tx.clone());
+    }
+
+    if let (Some(at), Some(offer)) = (expires_at, stored.first().and_then(|tx| tx.id)) {
+      transactions.expiries.insert(offer, at);
+    }
+
+    Ok(stored)
+  }
+
+  async fn spent_since(
+    &self,
+    user_id: &Uuid,
+    since: DateTime<Utc>,
+    excluding: Option<&Uuid>,
+  ) -> Result<i64, sqlx::Error> {
+    let transactions = self.lock();
+
+    Ok(
+      transactions
+        .all
+        .iter()
+        .filter(|tx| {
+          tx.sender == Some(*user_id)
+            && !tx.is_state
+            && tx.created_at >= since
+            && excluding.is_none_or(|id| tx.id != Some(*id))
+            && tx
+              .consumes
+              .and_then(|id| transactions.get(&id))
+              .is_some_and(|used| used.is_state)
+        })
+        .map(|tx| i64::from(tx.consumed_value))
+        .sum(),
+    )
+  }
+
+  async fn limit_overrides(&self, user_id: &Uuid) -> Result<LimitOverrides, sqlx::Error> {
+    Ok(self.lock().limits.get(user_id).copied().unwrap_or_default())
+  }
+
+  async fn set_limits(
+    &self,
+    user_id: &Uuid,
+    setter: LimitSetter,
+    limits: Limits,
+  ) -> Result<(), sqlx::Error> {
+    let mut transactions = self.lock();
+    let overrides = transactions.limits.entry(*user_id).or_default();
+
+    match setter {
+      LimitSetter::Admin => overrides.admin = limits,
+      LimitSetter::User => overrides.user = limits,
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,84 @@
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
+use sqlx::types::Uuid;
+
+use super::account::Account;
+use super::error::WalletError;
+use super::limits::{LimitOverrides, LimitSetter, Limits};
+use super::transaction::Transaction;
+
+mod memory;
+mod postgres;
+
+pub use memory::MemoryStore;
+pub use postgres::PostgresStore;
+
+///
+/// The storage backend of the ledger. OCSL operations (offering, accepting, minting, etc.)
+/// are built on top of these primitives, so they behave the same on any backend. Backends
+/// report missing transactions (or balances) as `sqlx::Error::RowNotFound`.
+///
+/// Backends MUST guarantee the following, which the operations rely on:
+/// - storing a batch of transactions is atomic: either all are stored, or none are,
+/// - a batch is rejected with `WalletError::AlreadyUsedTransaction` if any transaction it
+///   consumes or merges is already used (or doesn't exist), even under concurrency,
+/// - stored transactions get an id and a creation time, and the transactions they consume
+///   or merge are marked as `consumed` or `merged`,
+/// - each account has at most one live (unused) state.
+///
+pub trait LedgerStore: Clone + Send + Sync + 'static {
+  fn get(&self, id: &Uuid) -> impl Future<Output = Result<Transaction, sqlx::Error>> + Send;
+
+  fn find_balance(
+    &self,
+    account: &Account,
+  ) -> impl Future<Output = Result<Transaction, sqlx::Error>> + Send;
+
+  fn find_open_offers(
+    &self,
+    account: &Account,
+    offset: u32,
+    limit: u32,
+  ) -> impl Future<Output = Result<Vec<Transaction>, sqlx::Error>> + Send;
+
+  ///
+  /// Stores given transactions, returning them as stored, in the same order. If an expiry
+  /// is given, the first transaction (an offer) is set to expire at that time, atomically
+  /// with storing it (see `Ledger::offer_from_balance()`).
+  ///
+  fn store(
+    &self,
+    txs: Vec<Transaction>,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> impl Future<Output = Result<Vec<Transaction>, WalletError>> + Send;
+
+  ///
+  /// Returns how much given user has spent from their balance since given time, i.e. the
+  /// total of offers they made consuming their balance, optionally leaving out given offer.
+  ///
+  fn spent_since(
+    &self,
+    user_id: &Uuid,
+    since: DateTime<Utc>,
+    excluding: Option<&Uuid>,
+  ) -> impl Future<Output = Result<i64, sqlx::Error>> + Send;
+
+  ///
+  /// Returns the spending limits set for given user (see `LimitOverrides`).
+  ///
+  fn limit_overrides(
+    &self,
+    user_id: &Uuid,
+  ) -> impl Future<Output = Result<LimitOverrides, sqlx::Error>> + Send;
+
+  ///
+  /// Sets the spending limits of given user on behalf of given setter.
+  ///
+  fn set_limits(
+    &self,
+    user_id: &Uuid,
+    setter: LimitSetter,
+    limits: Limits,
+  ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+}
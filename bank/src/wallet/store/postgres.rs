@@ -0,0 +1,339 @@
+use chrono::{DateTime, Utc};
+use log::error;
+use sqlx::{postgres::Postgres, types::Uuid, FromRow, Pool, QueryBuilder};
+
+use super::super::account::Account;
+use super::super::error::WalletError;
+use super::super::limits::{LimitOverrides, LimitSetter, Limits};
+use super::super::transaction::Transaction;
+use super::LedgerStore;
+
+#[derive(Debug, Clone)]
+pub struct PostgresStore {
+  pub(in crate::wallet) pool: Pool<Postgres>,
+}
+
+impl PostgresStore {
+  pub fn new(pool: Pool<Postgres>) -> Self {
+    Self { pool }
+  }
+}
+
+impl LedgerStore for PostgresStore {
+  async fn get(&self, id: &Uuid) -> Result<Transaction, sqlx::Error> {
+    match sqlx::query_as!(
+      Transaction,
+      "
+        select * from transactions where id = $1
+      ",
+      id
+    )
+    .fetch_one(&self.pool)
+    .await
+    {
+      Ok(tx) => Ok(tx),
+      Err(e) => Err(e),
+    }
+  }
+
+  async fn find_balance(&self, account: &Account) -> Result<Transaction, sqlx::Error> {
+    let result = match account {
+      Account::User(user_id) => {
+        sqlx::query_as!(
+          Transaction,
+          "
+            select * from transactions
+            where receiver = $1
+              and is_state = true
+              and consumed is false and merged is false
+          ",
+          user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+      }
+      Account::System(sys_id) => {
+        sqlx::query_as!(
+          Transaction,
+          "
+            select * from transactions
+            where receiver_sys = $1
+              and is_state = true
+              and consumed is false and merged is false
+          ",
+          sys_id
+        )
+        .fetch_one(&self.pool)
+        .await
+      }
+      Account::Invalid => return Err(sqlx::Error::RowNotFound),
+    };
+
+    match result {
+      Ok(tx) => Ok(tx),
+      Err(e) => Err(e),
+    }
+  }
+
+  async fn find_open_offers(
+    &self,
+    account: &Account,
+    offset: u32,
+    limit: u32,
+  ) -> Result<Vec<Transaction>, sqlx::Error> {
+    let result = match account {
+      Account::User(user_id) => {
+        sqlx::query_as!(
+          Transaction,
+          "
+            select * from transactions
+            where receiver = $1
+              and consumed is false and merged is false
+            offset $2
+            limit $3
+          ",
+          user_id,
+          i64::from(offset),
+          i64::from(limit),
+        )
+        .fetch_all(&self.pool)
+        .await
+      }
+      Account::System(sys_id) => {
+        sqlx::query_as!(
+          Transaction,
+          "
+            select * from transactions
+            where receiver_sys = $1
+              and consumed is false and merged is false
+            offset $2
+            limit $3
+          ",
+          sys_id,
+          i64::from(offset),
+          i64::from(limit),
+        )
+        .fetch_all(&self.pool)
+        .await
+      }
+      Account::Invalid => return Err(sqlx::Error::RowNotFound),
+    };
+
+    match result {
+      Ok(txs) => Ok(txs),
+      Err(e) => Err(e),
+    }
+  }
+
+  ///
+  /// Stores given transactions within a single database transaction. Transactions
+  /// consumed or merged by the new ones are locked beforehand, so that concurrent
+  /// operations can't use the same transaction twice (e.g. double spending a balance).
+  /// The expiry of the offer, if any, is stored within the same database transaction.
+  ///
+  async fn store(
+    &self,
+    txs: Vec<Transaction>,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> Result<Vec<Transaction>, WalletError> {
+    let mut db = self.pool.begin().await.map_err(|err| {
+      error!("Failed to begin ledger transaction: {err:?}");
+      WalletError::Unknown
+    })?;
+
+    let mut used = txs
+      .iter()
+      .flat_map(|tx| [tx.consumes, tx.merges])
+      .flatten()
+      .collect::<Vec<_>>();
+    used.sort_unstable();
+    used.dedup();
+
+    if !used.is_empty() {
+      // locking in a consistent order avoids deadlocks between
+      // concurrent operations using overlapping transactions.
+      let usable = sqlx::query_scalar!(
+        "
+          select id from transactions
+          where id = any($1) and consumed is false and merged is false
+          order by id
+          for update
+        ",
+        &used,
+      )
+      .fetch_all(&mut *db)
+      .await
+      .map_err(|err| {
+        error!("Failed to lock transactions {used:?}: {err:?}");
+        WalletError::Unknown
+      })?;
+
+      if usable.len() != used.len() {
+        return Err(WalletError::AlreadyUsedTransaction);
+      }
+    }
+
+    let mut query = QueryBuilder::new(
+      "insert into transactions (
+        sender, sender_sys, receiver, receiver_sys,
+        consumes, consumed_value, merges, merged_value,
+        note, issued_by
+      )",
+    );
+
+    query.push_values(&txs, |mut b, tx| {
+      b.push_bind(tx.sender);
+      b.push_bind(tx.sender_sys.clone());
+      b.push_bind(tx.receiver);
+      b.push_bind(tx.receiver_sys.clone());
+      b.push_bind(tx.consumes);
+      b.push_bind(tx.consumed_value);
+      b.push_bind(tx.merges);
+      b.push_bind(tx.merged_value);
+      b.push_bind(tx.note.clone());
+      b.push_bind(tx.issued_by);
+    });
+
+    query.push("returning *");
+
+    let rows = query.build().fetch_all(&mut *db).await.map_err(|err| {
+      error!("Failed to store transactions: {err:?}");
+      WalletError::Unknown
+    })?;
+
+    // decoded before committing, so that nothing is stored if they can't be returned.
+    let stored = rows
+      .iter()
+      .map(Transaction::from_row)
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|err| {
+        error!("Failed to read stored transactions: {err:?}");
+        WalletError::Unknown
+      })?;
+
+    if let Some(at) = expires_at {
+      let offer = stored.first().and_then(|tx| tx.id);
+      sqlx::query!(
+        "insert into offer_expiries (tx, expires_at) values ($1, $2)",
+        offer,
+        at,
+      )
+      .execute(&mut *db)
+      .await
+      .map_err(|err| {
+        error!("Failed to set expiry of offer {offer:?}: {err:?}");
+        WalletError::Unknown
+      })?;
+    }
+
+    db.commit().await.map_err(|err| {
+      error!("Failed to commit transactions: {err:?}");
+      WalletError::Unknown
+    })?;
+
+    Ok(stored)
+  }
+
+  async fn spent_since(
+    &self,
+    user_id: &Uuid,
+    since: DateTime<Utc>,
+    excluding: Option<&Uuid>,
+  ) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+      r#"
+        select coalesce(sum(tx.consumed_value), 0)::bigint as "spent!"
+        from transactions tx
+        join transactions used on used.id = tx.consumes
+        where tx.sender = $1
+          and tx.is_state is false
+          and used.is_state is true
+          and tx.created_at >= $2
+          and ($3::uuid is null or tx.id <> $3)
+      "#,
+      user_id,
+      since,
+      excluding,
+    )
+    .fetch_one(&self.pool)
+    .await
+  }
+
+  async fn limit_overrides(&self, user_id: &Uuid) -> Result<LimitOverrides, sqlx::Error> {
+    let limits = sqlx::query!(
+      "
+        select admin_daily, admin_per_transaction, user_daily, user_per_transaction
+        from spending_limits where user_id = $1
+      ",
+      user_id,
+    )
+    .fetch_optional(&self.pool)
+    .await?;
+
+    let limit = |value: Option<i32>| value.and_then(|value| u32::try_from(value).ok());
+
+    Ok(
+      limits.map_or_else(LimitOverrides::default, |limits| LimitOverrides {
+        admin: Limits {
+          daily: limit(limits.admin_daily),
+          per_transaction: limit(limits.admin_per_transaction),
+        },
+        user: Limits {
+          daily: limit(limits.user_daily),
+          per_transaction: limit(limits.user_per_transaction),
+        },
+      }),
+    )
+  }
+
+  async fn set_limits(
+    &self,
+    user_id: &Uuid,
+    setter: LimitSetter,
+    limits: Limits,
+  ) -> Result<(), sqlx::Error> {
+    let daily = limits.daily.and_then(|limit| i32::try_from(limit).ok());
+    let per_transaction = limits
+      .per_transaction
+      .and_then(|limit| i32::try_from(limit).ok());
+
+    match setter {
+      LimitSetter::Admin => {
+        sqlx::query!(
+          "
+            insert into spending_limits (user_id, admin_daily, admin_per_transaction)
+            values ($1, $2, $3)
+            on conflict (user_id) do update set
+              admin_daily = excluded.admin_daily,
+              admin_per_transaction = excluded.admin_per_transaction,
+              updated_at = now()
+          ",
+          user_id,
+          daily,
+          per_transaction,
+        )
+        .execute(&self.pool)
+        .await?;
+      }
+      LimitSetter::User => {
+        sqlx::query!(
+          "
+            insert into spending_limits (user_id, user_daily, user_per_transaction)
+            values ($1, $2, $3)
+            on conflict (user_id) do update set
+              user_daily = excluded.user_daily,
+              user_per_transaction = excluded.user_per_transaction,
+              updated_at = now()
+          ",
+          user_id,
+          daily,
+          per_transaction,
+        )
+        .execute(&self.pool)
+        .await?;
+      }
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,497 @@
+use futures::future::join_all;
+use sqlx::{types::Uuid, PgPool};
+
+use super::allowance::Allowance;
+use super::config::Config;
+use super::error::WalletError;
+use super::limits::{LimitSetter, Limits, SpendingLimits};
+use super::store::MemoryStore;
+use super::{Account, Ledger, Transaction};
+use crate::auth::{user::VerificationStatus, AuthenticatedUser};
+use crate::{commit_tx, tx};
+
+fn config() -> Config {
+  Config {
+    initial_balance: 10,
+    daily_send_limit: 100,
+    allowance: Allowance::default(),
+    spending: SpendingLimits::default(),
+    step_up_threshold: 0,
+  }
+}
+
+fn ledger(pool: PgPool) -> Ledger {
+  Ledger::new(config(), pool)
+}
+
+fn memory_ledger() -> (Ledger<MemoryStore>, MemoryStore) {
+  let store = MemoryStore::default();
+  (Ledger::with_backend(config(), store.clone()), store)
+}
+
+fn limited_ledger() -> Ledger<MemoryStore> {
+  let config = Config {
+    spending: SpendingLimits {
+      daily: 6,
+      per_transaction: 4,
+    },
+    ..config()
+  };
+
+  Ledger::with_backend(config, MemoryStore::default())
+}
+
+fn issuer() -> AuthenticatedUser {
+  AuthenticatedUser {
+    id: Uuid::new_v4(),
+    email: "jane@example.com".to_string(),
+    first_name: "Jane".to_string(),
+    last_name: "Doe".to_string(),
+    verification: VerificationStatus::default(),
+    token: String::new(),
+  }
+}
+
+///
+/// Asserts that no coins were created or destroyed, other than
+/// by issuance (initial balances or minting).
+///
+fn assert_conserved(store: &MemoryStore) {
+  let transactions = store.transactions();
+  let issued: u32 = transactions
+    .iter()
+    .filter(|tx| tx.is_state && tx.consumes.is_none())
+    .map(|tx| u32::try_from(tx.consumed_value).unwrap())
+    .sum();
+  let live: u32 = transactions
+    .iter()
+    .filter(|tx| !tx.is_used())
+    .map(Transaction::total)
+    .sum();
+
+  assert_eq!(issued, live);
+}
+
+async fn user(pool: &PgPool) -> AuthenticatedUser {
+  let id = Uuid::new_v4();
+  let email = format!("{id}@example.com");
+
+  sqlx::query!(
+    "insert into users (id, first_name, last_name, email) values ($1, 'Jane', 'Doe', $2)",
+    id,
+    email,
+  )
+  .execute(pool)
+  .await
+  .unwrap();
+
+  AuthenticatedUser {
+    id,
+    email,
+    first_name: "Jane".to_string(),
+    last_name: "Doe".to_string(),
+    verification: VerificationStatus::default(),
+    token: String::new(),
+  }
+}
+
+#[sqlx::test]
+async fn rejects_spending_a_stale_balance(pool: PgPool) {
+  let ledger = ledger(pool.clone());
+  let alice = user(&pool).await;
+  let bob = user(&pool).await;
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  let balance = ledger.balance_or_init(&from, None, &alice).await.unwrap();
+
+  commit_tx![
+    tx! { &from => &to; using &balance, 10; by &alice };
+    to ledger
+  ]
+  .unwrap();
+
+  let res = commit_tx![
+    tx! { &from => &to; using &balance, 10; by &alice };
+    to ledger
+  ];
+
+  assert!(matches!(res, Err(WalletError::AlreadyUsedTransaction)));
+  assert!(ledger.verify().await.unwrap().ok);
+}
+
+#[sqlx::test]
+async fn concurrent_offers_do_not_double_spend(pool: PgPool) {
+  let ledger = ledger(pool.clone());
+  let alice = user(&pool).await;
+  let bob = user(&pool).await;
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  ledger.balance_or_init(&from, None, &alice).await.unwrap();
+
+  let results =
+    join_all((0..8).map(|_| ledger.offer_from_balance(&from, &to, 4, None, None, &alice))).await;
+
+  let offered: u32 = results
+    .iter()
+    .filter_map(|res| res.as_ref().ok())
+    .map(|res| res.offer.total())
+    .sum();
+  let balance = ledger.find_balance(&from).await.unwrap();
+
+  assert!(results.iter().all(|res| matches!(
+    res,
+    Ok(_) | Err(WalletError::AlreadyUsedTransaction | WalletError::InsufficientFunds)
+  )));
+  assert!(offered <= 10);
+  assert_eq!(offered + balance.total(), 10);
+  assert!(ledger.verify().await.unwrap().ok);
+}
+
+#[sqlx::test]
+async fn concurrent_initialization_creates_a_single_balance(pool: PgPool) {
+  let ledger = ledger(pool.clone());
+  let alice = user(&pool).await;
+  let account = Account::of_user(&alice.id);
+
+  let balances = join_all((0..8).map(|_| ledger.balance_or_init(&account, None, &alice))).await;
+
+  let balance = ledger.find_balance(&account).await.unwrap();
+  assert!(balances
+    .into_iter()
+    .all(|res| res.unwrap().id == balance.id));
+  assert!(ledger.verify().await.unwrap().ok);
+}
+
+#[sqlx::test]
+async fn an_offer_is_either_accepted_or_rejected(pool: PgPool) {
+  let ledger = ledger(pool.clone());
+  let alice = user(&pool).await;
+  let bob = user(&pool).await;
+
+  let offer = ledger
+    .offer_from_balance(
+      &Account::of_user(&alice.id),
+      &Account::of_user(&bob.id),
+      4,
+      None,
+      None,
+      &alice,
+    )
+    .await
+    .unwrap()
+    .offer;
+  ledger
+    .balance_or_init(&Account::of_user(&bob.id), None, &bob)
+    .await
+    .unwrap();
+
+  let (accepted, rejected) = tokio::join!(
+    ledger.accept_offer(&offer, &bob),
+    ledger.reject_offer(&offer, None, &bob),
+  );
+
+  assert!(accepted.is_ok() != rejected.is_ok());
+  assert!(matches!(
+    accepted.err().or(rejected.err()),
+    Some(WalletError::AlreadyUsedTransaction)
+  ));
+  assert!(ledger.verify().await.unwrap().ok);
+}
+
+#[tokio::test]
+async fn accepting_an_offer_moves_funds() {
+  let (ledger, store) = memory_ledger();
+  let (alice, bob) = (issuer(), issuer());
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  let offered = ledger
+    .offer_from_balance(&from, &to, 4, None, None, &alice)
+    .await
+    .unwrap();
+  let merged = ledger.accept_offer(&offered.offer, &bob).await.unwrap();
+
+  assert_eq!(offered.rest.total(), 6);
+  assert_eq!(merged.total(), 14);
+  assert_eq!(ledger.find_balance(&to).await.unwrap().id, merged.id);
+  assert!(matches!(
+    ledger.accept_offer(&offered.offer, &bob).await,
+    Err(WalletError::AlreadyUsedTransaction)
+  ));
+  assert_conserved(&store);
+}
+
+#[tokio::test]
+async fn partially_accepting_an_offer_returns_the_rest() {
+  let (ledger, store) = memory_ledger();
+  let (alice, bob) = (issuer(), issuer());
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  let offer = ledger
+    .offer_from_balance(&from, &to, 4, None, None, &alice)
+    .await
+    .unwrap()
+    .offer;
+  let result = ledger
+    .partially_accept_offer(&offer, 3, None, &bob)
+    .await
+    .unwrap();
+
+  assert_eq!(result.merged.total(), 13);
+  assert_eq!(result.returned.total(), 1);
+  assert_eq!(result.returned.receiver_account(), from);
+
+  let merged = ledger.accept_offer(&result.returned, &alice).await.unwrap();
+  assert_eq!(merged.total(), 7);
+  assert_conserved(&store);
+}
+
+#[tokio::test]
+async fn rejected_and_rescinded_offers_return_to_the_sender() {
+  let (ledger, store) = memory_ledger();
+  let (alice, bob) = (issuer(), issuer());
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  let rejected = ledger
+    .offer_from_balance(&from, &to, 2, None, None, &alice)
+    .await
+    .unwrap()
+    .offer;
+  let rescinded = ledger
+    .offer_from_balance(&from, &to, 3, None, None, &alice)
+    .await
+    .unwrap()
+    .offer;
+
+  let revert = ledger.reject_offer(&rejected, None, &bob).await.unwrap();
+  assert_eq!(revert.receiver_account(), from);
+  assert_eq!(revert.total(), 2);
+
+  let merged = ledger.rescind_offer(&rescinded, &alice).await.unwrap();
+  assert_eq!(merged.total(), 8);
+  assert!(matches!(
+    ledger.reject_offer(&rescinded, None, &bob).await,
+    Err(WalletError::AlreadyUsedTransaction)
+  ));
+  assert_conserved(&store);
+}
+
+#[tokio::test]
+async fn offers_can_not_spend_more_than_the_balance() {
+  let (ledger, store) = memory_ledger();
+  let (alice, bob) = (issuer(), issuer());
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  let balance = ledger.balance_or_init(&from, None, &alice).await.unwrap();
+  commit_tx![
+    tx! { &from => &to; using &balance, 10; by &alice };
+    to ledger
+  ]
+  .unwrap();
+
+  assert!(matches!(
+    commit_tx![
+      tx! { &from => &to; using &balance, 10; by &alice };
+      to ledger
+    ],
+    Err(WalletError::AlreadyUsedTransaction)
+  ));
+
+  let results =
+    join_all((0..8).map(|_| ledger.offer_from_balance(&to, &from, 4, None, None, &bob))).await;
+  assert_eq!(results.iter().filter(|res| res.is_ok()).count(), 2);
+  assert!(matches!(
+    ledger
+      .offer_from_balance(&to, &from, 3, None, None, &bob)
+      .await,
+    Err(WalletError::InsufficientFunds)
+  ));
+  assert_conserved(&store);
+}
+
+#[tokio::test]
+async fn injecting_mints_from_the_mint_account() {
+  let (ledger, store) = memory_ledger();
+  let admin = issuer();
+  let alice = Account::of_user(&issuer().id);
+  let bank = Account::of_sys_user("bank");
+
+  ledger.inject(&alice, 5, None, &admin).await.unwrap();
+  ledger.inject(&bank, 3, None, &admin).await.unwrap();
+
+  let offers = ledger.find_open_offers(&alice, 0, 32).await.unwrap();
+  assert_eq!(offers.len(), 1);
+  assert_eq!(offers[0].sender_account(), Account::mint());
+  assert_eq!(ledger.find_balance(&bank).await.unwrap().total(), 13);
+  assert_eq!(
+    ledger.find_balance(&Account::mint()).await.unwrap().total(),
+    0
+  );
+  assert!(matches!(
+    ledger.inject(&Account::mint(), 3, None, &admin).await,
+    Err(WalletError::UnauthorizedTransaction)
+  ));
+  assert_conserved(&store);
+}
+
+#[tokio::test]
+async fn expiring_offers_record_their_expiry() {
+  let (ledger, store) = memory_ledger();
+  let (alice, bob) = (issuer(), issuer());
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+  let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+
+  let offer = ledger
+    .offer_from_balance(&from, &to, 4, None, Some(expires_at), &alice)
+    .await
+    .unwrap()
+    .offer;
+
+  assert_eq!(store.expiry_of(&offer.id.unwrap()), Some(expires_at));
+  assert!(matches!(
+    ledger
+      .offer_from_balance(
+        &from,
+        &Account::of_sys_user("bank"),
+        1,
+        None,
+        Some(expires_at),
+        &alice
+      )
+      .await,
+    Err(WalletError::ErroneousTransaction)
+  ));
+}
+
+#[tokio::test]
+async fn offers_can_not_exceed_spending_limits() {
+  let ledger = limited_ledger();
+  let (alice, bob) = (issuer(), issuer());
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  assert!(matches!(
+    ledger
+      .offer_from_balance(&from, &to, 5, None, None, &alice)
+      .await,
+    Err(WalletError::LimitExceeded { resets_at: None })
+  ));
+
+  let offer = ledger
+    .offer_from_balance(&from, &to, 4, None, None, &alice)
+    .await
+    .unwrap()
+    .offer;
+  ledger
+    .offer_from_balance(&from, &to, 2, None, None, &alice)
+    .await
+    .unwrap();
+
+  // returned offers don't count as spending, but
+  // neither do they lift the spending of the day.
+  ledger.reject_offer(&offer, None, &bob).await.unwrap();
+  assert!(matches!(
+    ledger
+      .offer_from_balance(&from, &to, 1, None, None, &alice)
+      .await,
+    Err(WalletError::LimitExceeded { resets_at: Some(_) })
+  ));
+  assert_eq!(
+    ledger.spending_status(&alice.id, None).await.unwrap().spent,
+    6
+  );
+
+  ledger
+    .offer_from_balance(&to, &from, 4, None, None, &bob)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn admins_override_and_users_lower_spending_limits() {
+  let ledger = limited_ledger();
+  let (alice, bob) = (issuer(), issuer());
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  let status = ledger
+    .set_spending_limits(
+      &alice.id,
+      LimitSetter::Admin,
+      Limits {
+        daily: Some(0),
+        per_transaction: Some(8),
+      },
+    )
+    .await
+    .unwrap();
+  assert_eq!((status.daily, status.per_transaction), (None, Some(8)));
+
+  ledger
+    .offer_from_balance(&from, &to, 7, None, None, &alice)
+    .await
+    .unwrap();
+
+  let lowered = Limits {
+    daily: None,
+    per_transaction: Some(2),
+  };
+  ledger
+    .set_spending_limits(&alice.id, LimitSetter::User, lowered)
+    .await
+    .unwrap();
+  assert!(matches!(
+    ledger
+      .offer_from_balance(&from, &to, 3, None, None, &alice)
+      .await,
+    Err(WalletError::LimitExceeded { resets_at: None })
+  ));
+
+  // users can't raise their limits above those in effect.
+  let raised = Limits {
+    daily: None,
+    per_transaction: Some(20),
+  };
+  let status = ledger
+    .set_spending_limits(&alice.id, LimitSetter::User, raised)
+    .await
+    .unwrap();
+  assert_eq!(status.per_transaction, Some(8));
+  ledger
+    .offer_from_balance(&from, &to, 3, None, None, &alice)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn step_up_is_required_once_spending_of_the_day_exceeds_the_threshold() {
+  let config = Config {
+    step_up_threshold: 5,
+    ..config()
+  };
+  let ledger = Ledger::with_backend(config, MemoryStore::default());
+  let (alice, bob) = (issuer(), issuer());
+  let (from, to) = (Account::of_user(&alice.id), Account::of_user(&bob.id));
+
+  assert!(!ledger
+    .check_offer_from_balance(&from, 3, &alice)
+    .await
+    .unwrap());
+  ledger
+    .offer_from_balance(&from, &to, 3, None, None, &alice)
+    .await
+    .unwrap();
+
+  // small offers add up to the threshold.
+  assert!(!ledger
+    .check_offer_from_balance(&from, 2, &alice)
+    .await
+    .unwrap());
+  assert!(ledger
+    .check_offer_from_balance(&from, 3, &alice)
+    .await
+    .unwrap());
+
+  // offers that wouldn't go through fail before asking for a step-up.
+  assert!(matches!(
+    ledger.check_offer_from_balance(&from, 8, &alice).await,
+    Err(WalletError::InsufficientFunds)
+  ));
+}
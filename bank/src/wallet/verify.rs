@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::types::Uuid;
+
+use super::ledger::Ledger;
+
+///
+/// Invariants of the OCSL ledger that can't be (efficiently) expressed
+/// as SQL constraints, and hence are verified separately:
+/// - `multiple_live_states`: each account must have at most one live (unused) state,
+/// - `value_not_conserved`: transactions consuming (or merging) another transaction must
+///   consume (or merge) its whole value, no more, no less,
+/// - `double_consumption`: each transaction can only be used once, i.e. consumed by a
+///   single commit of the ledger, or merged into a single new state,
+/// - `negative_value`: transactions can't carry negative values,
+/// - `inconsistent_flags`: the `consumed` and `merged` flags of a transaction must match
+///   whether it is actually consumed or merged by other transactions.
+///
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Invariant {
+  MultipleLiveStates,
+  ValueNotConserved,
+  DoubleConsumption,
+  NegativeValue,
+  InconsistentFlags,
+}
+
+///
+/// A violation of some invariant, alongside the ids of the transactions involved.
+///
+#[derive(Serialize, Debug, Clone)]
+pub struct Violation {
+  pub invariant: Invariant,
+  pub transactions: Vec<Uuid>,
+  pub details: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct VerificationReport {
+  pub ok: bool,
+  pub checked: i64,
+  pub checked_at: DateTime<Utc>,
+  pub violations: Vec<Violation>,
+}
+
+struct Finding {
+  transactions: Vec<Uuid>,
+  details: String,
+}
+
+impl Finding {
+  fn violates(self, invariant: Invariant) -> Violation {
+    Violation {
+      invariant,
+      transactions: self.transactions,
+      details: self.details,
+    }
+  }
+}
+
+impl Ledger {
+  ///
+  /// Walks the whole ledger, checking invariants that the database doesn't enforce
+  /// (see `Invariant`). All checks run on the same snapshot of the ledger, so
+  /// transactions committed meanwhile don't cause false positives.
+  ///
+  /// ### Returns:
+  /// a report of all found violations, which is `ok` if there are none.
+  ///
+  #[allow(clippy::too_many_lines)]
+  pub async fn verify(&self) -> Result<VerificationReport, sqlx::Error> {
+    let mut tx = self.backend.pool.begin().await?;
+    sqlx::query!("set transaction isolation level repeatable read, read only")
+      .execute(&mut *tx)
+      .await?;
+
+    let checked_at = Utc::now();
+    let checked = sqlx::query_scalar!(r#"select count(*) as "count!" from transactions"#)
+      .fetch_one(&mut *tx)
+      .await?;
+
+    let mut violations = vec![];
+
+    violations.extend(
+      sqlx::query_as!(
+        Finding,
+        r#"
+          select
+            array_agg(id order by created_at) as "transactions!",
+            format(
+              'account %s has %s live states',
+              coalesce('[u]' || receiver::text, '[s]' || receiver_sys), count(*)
+            ) as "details!"
+          from transactions
+          where is_state and not consumed and not merged
+          group by receiver, receiver_sys
+          having count(*) > 1
+        "#
+      )
+      .fetch_all(&mut *tx)
+      .await?
+      .into_iter()
+      .map(|f| f.violates(Invariant::MultipleLiveStates)),
+    );
+
+    violations.extend(
+      sqlx::query_as!(
+        Finding,
+        r#"
+          select
+            array[used.id] || array_agg(child.id order by child.created_at) as "transactions!",
+            format(
+              'consumed %s out of %s',
+              sum(child.consumed_value), used.consumed_value + used.merged_value
+            ) as "details!"
+          from transactions used
+          join transactions child on child.consumes = used.id
+          group by used.id
+          having sum(child.consumed_value) <> used.consumed_value + used.merged_value
+        "#
+      )
+      .fetch_all(&mut *tx)
+      .await?
+      .into_iter()
+      .map(|f| f.violates(Invariant::ValueNotConserved)),
+    );
+
+    violations.extend(
+      sqlx::query_as!(
+        Finding,
+        r#"
+          select
+            array[state.id, merger.id] as "transactions!",
+            format(
+              'merged %s out of %s',
+              merger.merged_value, state.consumed_value + state.merged_value
+            ) as "details!"
+          from transactions merger
+          join transactions state on state.id = merger.merges
+          where merger.merged_value <> state.consumed_value + state.merged_value
+        "#
+      )
+      .fetch_all(&mut *tx)
+      .await?
+      .into_iter()
+      .map(|f| f.violates(Invariant::ValueNotConserved)),
+    );
+
+    // transactions committed together share their creation time,
+    // so consumers created at different times were committed separately.
+    violations.extend(
+      sqlx::query_as!(
+        Finding,
+        r#"
+          with usages as (
+            select consumes as id, id as user_id, created_at, 'consumed' as kind
+            from transactions where consumes is not null
+            union all
+            select merges as id, id as user_id, created_at, 'merged' as kind
+            from transactions where merges is not null
+          )
+          select
+            array[id] || array_agg(user_id order by created_at) as "transactions!",
+            format(
+              'used by %s separate commits (%s)',
+              count(distinct (kind, created_at)), string_agg(distinct kind, ', ')
+            ) as "details!"
+          from usages
+          group by id
+          having count(distinct (kind, created_at)) > 1
+        "#
+      )
+      .fetch_all(&mut *tx)
+      .await?
+      .into_iter()
+      .map(|f| f.violates(Invariant::DoubleConsumption)),
+    );
+
+    violations.extend(
+      sqlx::query_as!(
+        Finding,
+        r#"
+          select
+            array[id] as "transactions!",
+            format('consumed value %s, merged value %s', consumed_value, merged_value) as "details!"
+          from transactions
+          where consumed_value < 0 or merged_value < 0
+        "#
+      )
+      .fetch_all(&mut *tx)
+      .await?
+      .into_iter()
+      .map(|f| f.violates(Invariant::NegativeValue)),
+    );
+
+    violations.extend(
+      sqlx::query_as!(
+        Finding,
+        r#"
+          select
+            array[tx.id] as "transactions!",
+            format(
+              'marked consumed: %s, merged: %s, but consumed: %s, merged: %s',
+              tx.consumed, tx.merged, consumer.id is not null, merger.id is not null
+            ) as "details!"
+          from transactions tx
+          left join (select distinct consumes as id from transactions) consumer on consumer.id = tx.id
+          left join (select distinct merges as id from transactions) merger on merger.id = tx.id
+          where tx.consumed <> (consumer.id is not null)
+             or tx.merged <> (merger.id is not null)
+        "#
+      )
+      .fetch_all(&mut *tx)
+      .await?
+      .into_iter()
+      .map(|f| f.violates(Invariant::InconsistentFlags)),
+    );
+
+    tx.commit().await?;
+
+    Ok(VerificationReport {
+      ok: violations.is_empty(),
+      checked,
+      checked_at,
+      violations,
+    })
+  }
+}
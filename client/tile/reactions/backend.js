@@ -25,7 +25,7 @@ export const reactions = async (tile) => {
 
   const summary = await res.json()
   return {
-    likes: summary.like_count,
+    likes: summary.counts[REACTIONS.LIKE] ?? 0,
     likedByUser: summary.viewer_reaction === REACTIONS.LIKE,
   }
 }